@@ -18,6 +18,12 @@ pub fn downloads_dir() -> std::path::PathBuf {
     std::path::PathBuf::from(".flatpak-builder/downloads")
 }
 
+/// Return the path of the download `name` with the checksum `sha` in
+/// the top-level `top_dir`, as flatpak-builder stores it.
+pub fn download_path(top_dir: &std::path::Path, name: &str, sha: &str) -> std::path::PathBuf {
+    top_dir.join(downloads_dir()).join(sha).join(name)
+}
+
 /// Return the directory for the git repositories relative to the top-level.
 pub fn git_dir() -> std::path::PathBuf {
     std::path::PathBuf::from(".flatpak-builder/git")
@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
 use clap::Parser;
@@ -10,6 +11,7 @@ use serde_json::Value as JsonValue;
 use url::Url;
 
 use crate::builder;
+use crate::download::{self, Checksum};
 use crate::project::Project;
 use crate::{anyerror, error::Context, Error, Result};
 
@@ -33,6 +35,13 @@ pub struct Args {
     /// Cleanup all. Includes -d, -b
     #[arg(short = 'a')]
     all: bool,
+    /// Verify the downloads checksums instead of removing the unused
+    /// ones.
+    #[arg(long)]
+    verify: bool,
+    /// Delete the bad downloads found by --verify.
+    #[arg(long, requires = "verify")]
+    delete_bad: bool,
 }
 
 /// Result of cleanup.
@@ -87,22 +96,14 @@ fn cleanup_builds(dry_run: bool, verbose: bool) -> Result<CleanupResult> {
     Ok(CleanupResult::NothingToClean)
 }
 
-fn cleanup_downloads(dry_run: bool, verbose: bool) -> Result<CleanupResult> {
-    let current_dir = std::env::current_dir().context("Get current dir")?;
-    let project = Project::open(&current_dir).context("Open project")?;
-
-    // Get download dir
-    let downloads_dir = current_dir.join(crate::builder::downloads_dir());
-    if !downloads_dir.exists() || !downloads_dir.is_dir() {
-        if verbose {
-            println!("No downloads directory.");
-        }
-        return Ok(CleanupResult::NothingToClean);
-    }
-
-    // List downloads
+/// List the files in the downloads, by checksum.
+fn list_downloads(
+    downloads_dir: &Path,
+    current_dir: &Path,
+    verbose: bool,
+) -> Result<MultiMap<OsString, PathBuf>> {
     let mut downloads = MultiMap::new();
-    if let Ok(dir) = std::fs::read_dir(&downloads_dir) {
+    if let Ok(dir) = std::fs::read_dir(downloads_dir) {
         for entry in dir {
             let path = entry.context("Get dir entry")?.path();
             if !path.is_dir() {
@@ -116,7 +117,7 @@ fn cleanup_downloads(dry_run: bool, verbose: bool) -> Result<CleanupResult> {
                             continue;
                         }
                         if verbose {
-                            let path = path.strip_prefix(&current_dir)?;
+                            let path = path.strip_prefix(current_dir)?;
                             println!("Found {path:?} (checksum {name:?})");
                         }
                         downloads.insert(name.to_owned(), path.canonicalize().unwrap());
@@ -126,6 +127,24 @@ fn cleanup_downloads(dry_run: bool, verbose: bool) -> Result<CleanupResult> {
         }
     }
 
+    Ok(downloads)
+}
+
+fn cleanup_downloads(dry_run: bool, verbose: bool) -> Result<CleanupResult> {
+    let current_dir = std::env::current_dir().context("Get current dir")?;
+    let project = Project::open(&current_dir).context("Open project")?;
+
+    // Get download dir
+    let downloads_dir = current_dir.join(crate::builder::downloads_dir());
+    if !downloads_dir.exists() || !downloads_dir.is_dir() {
+        if verbose {
+            println!("No downloads directory.");
+        }
+        return Ok(CleanupResult::NothingToClean);
+    }
+
+    let mut downloads = list_downloads(&downloads_dir, &current_dir, verbose)?;
+
     // Get git dir
     // let git_dir = current_dir.join(crate::builder::git_dir());
     // List git repos (canonicalize from dir name). Problem: managing submodules.
@@ -152,10 +171,9 @@ fn cleanup_downloads(dry_run: bool, verbose: bool) -> Result<CleanupResult> {
             let sha256 = source.get("sha256")?.as_str()?;
             let url = source.get("url")?.as_str()?;
             let url = Url::parse(url).ok()?;
-            let names: Vec<_> = url.path_segments()?.collect::<Vec<_>>();
-            let name = names.iter().last()?.to_string();
-            let path = build_download_path(&current_dir, &name, sha256);
-            Some((std::ffi::OsString::from(sha256), path))
+            let name = download::file_name(&url)?;
+            let path = builder::download_path(&current_dir, &name, sha256);
+            Some((OsString::from(sha256), path))
         })
         .for_each(|source| {
             if let Some(v) = downloads.get_vec_mut(&source.0) {
@@ -187,10 +205,143 @@ fn cleanup_downloads(dry_run: bool, verbose: bool) -> Result<CleanupResult> {
     Ok(CleanupResult::Success(total_size))
 }
 
-fn build_download_path(current_dir: &Path, name: &str, sha: &str) -> PathBuf {
+/// The download of a source checked against the manifest.
+#[derive(Debug, PartialEq)]
+enum SourceCheck {
+    /// Not an archive or a file source with an URL and a checksum.
+    Skipped,
+    /// The download, by name, isn't in the downloads.
+    Missing(String),
+    /// The download matches all the checksums of the source.
+    Verified(PathBuf),
+    /// The download doesn't match the checksum of the source.
+    Mismatch(PathBuf, Checksum),
+}
+
+/// Check the download of the archive or file `source`, in the project
+/// at `top_dir`, against all its declared checksums. The download is
+/// named after the URL, like `download::download` does.
+fn check_source(top_dir: &Path, source: &JsonValue) -> Result<SourceCheck> {
+    let type_ = source.get("type").and_then(JsonValue::as_str);
+    if type_ != Some("archive") && type_ != Some("file") {
+        return Ok(SourceCheck::Skipped);
+    }
+    let Some(name) = source
+        .get("url")
+        .and_then(JsonValue::as_str)
+        .and_then(|url| Url::parse(url).ok())
+        .and_then(|url| download::file_name(&url))
+    else {
+        return Ok(SourceCheck::Skipped);
+    };
+    let declared: Vec<_> = Checksum::ALL
+        .iter()
+        .filter_map(|checksum| {
+            let value = source.get(checksum.key())?.as_str()?;
+            Some((*checksum, value.to_lowercase()))
+        })
+        .collect();
+    if declared.is_empty() {
+        return Ok(SourceCheck::Skipped);
+    }
+    let Some(path) = declared
+        .iter()
+        .map(|(_, value)| builder::download_path(top_dir, &name, value))
+        .find(|path| path.is_file())
+        .and_then(|path| path.canonicalize().ok())
+    else {
+        return Ok(SourceCheck::Missing(name));
+    };
+    for (checksum, value) in &declared {
+        if checksum.hash_file(&path)? != *value {
+            return Ok(SourceCheck::Mismatch(path, *checksum));
+        }
+    }
+
+    Ok(SourceCheck::Verified(path))
+}
+
+/// Re-hash the downloads and check them against their checksum
+/// directory, and against the checksums of the sources declared in the
+/// manifest. Delete the bad ones if `delete`, unless `dry_run`.
+fn verify_downloads(dry_run: bool, verbose: bool, delete: bool) -> Result<CleanupResult> {
+    let current_dir = std::env::current_dir().context("Get current dir")?;
+    let project = Project::open(&current_dir).context("Open project")?;
+
     let downloads_dir = current_dir.join(crate::builder::downloads_dir());
-    let download_path = downloads_dir.join(sha);
-    download_path.join(name)
+    if !downloads_dir.exists() || !downloads_dir.is_dir() {
+        if verbose {
+            println!("No downloads directory.");
+        }
+        return Ok(CleanupResult::NothingToClean);
+    }
+    let downloads = list_downloads(&downloads_dir, &current_dir, verbose)?;
+
+    // The bad downloads, with the reason.
+    let mut bad = Vec::<(PathBuf, String)>::new();
+    for (name, path) in downloads.flat_iter() {
+        let name = name.to_string_lossy();
+        let rel_path = path.strip_prefix(&current_dir).unwrap_or(path);
+        let Some(checksum) = Checksum::from_hex(&name) else {
+            if verbose {
+                println!("Skipping {rel_path:?}, unknown checksum");
+            }
+            continue;
+        };
+        if checksum.hash_file(path)? != name {
+            bad.push((
+                path.clone(),
+                format!("{} doesn't match, corrupted or truncated", checksum.key()),
+            ));
+        } else if verbose {
+            println!("Verified {rel_path:?}");
+        }
+    }
+
+    match declared_sources(&project) {
+        Ok(sources) => {
+            for source in &sources {
+                match check_source(&current_dir, source)? {
+                    SourceCheck::Missing(name) if verbose => {
+                        println!("{name} isn't downloaded");
+                    }
+                    SourceCheck::Mismatch(path, checksum)
+                        if !bad.iter().any(|(bad_path, _)| *bad_path == path) =>
+                    {
+                        bad.push((
+                            path,
+                            format!("{} doesn't match the manifest", checksum.key()),
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Err(err) => eprintln!("Warning: can't check the manifest sources: {err}"),
+    }
+
+    let mut total_size = 0_u64;
+    for (path, reason) in &bad {
+        let rel_path = path.strip_prefix(&current_dir).unwrap_or(path);
+        println!("Bad download {rel_path:?}: {reason}");
+        if !delete {
+            continue;
+        }
+        total_size += std::fs::metadata(path)?.len();
+        if dry_run {
+            println!("Would delete {rel_path:?}");
+        } else {
+            if verbose {
+                println!("Deleting {rel_path:?}");
+            }
+            std::fs::remove_file(path)?;
+        }
+    }
+    if !delete && !bad.is_empty() {
+        return Err(anyerror!(format!("{} bad download(s)", bad.len())));
+    }
+
+    Ok(CleanupResult::Success(total_size))
 }
 
 /// Get all the sources declared in the manifest.
@@ -242,9 +393,18 @@ pub fn run_builds(args: &Args) -> Result<CleanupResult> {
 }
 
 pub fn run_downloads(args: &Args) -> Result<CleanupResult> {
-    let r = cleanup_downloads(args.dry_run, args.verbose);
+    let r = if args.verify {
+        verify_downloads(args.dry_run, args.verbose, args.delete_bad)
+    } else {
+        cleanup_downloads(args.dry_run, args.verbose)
+    };
     if let Ok(result) = &r {
         match result {
+            CleanupResult::Success(_) if args.verify && !args.delete_bad => {
+                if !args.silent {
+                    println!("Downloads verified.");
+                }
+            }
             CleanupResult::Success(total_size) => {
                 if args.dry_run {
                     println!(
@@ -270,7 +430,7 @@ pub fn run_downloads(args: &Args) -> Result<CleanupResult> {
 
 /// Run the command
 pub fn run(args: Args) -> Result<()> {
-    if args.all || args.downloads {
+    if args.all || args.downloads || args.verify {
         run_downloads(&args)?;
     }
     if args.all || args.builds {
@@ -279,3 +439,52 @@ pub fn run(args: Args) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const SHA1: &str = "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d";
+    const SHA512: &str = "9b71d224bd62f3785d96d46ad3ea3d73319bfbc2890caadae2dff72519673ca72323c3d99ba5c11d7c7acc6e14b8c5da0c4663475c2e5c3adef46f73bcdec043";
+
+    #[test]
+    fn test_check_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let top_dir = dir.path().canonicalize().unwrap();
+        let path = builder::download_path(&top_dir, "app-1.0.tar.gz", SHA512);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "hello").unwrap();
+        let url = "https://example.org/app-1.0.tar.gz";
+
+        let source = json!({ "type": "archive", "url": url, "sha512": SHA512, "sha1": SHA1 });
+        assert_eq!(
+            check_source(&top_dir, &source).unwrap(),
+            SourceCheck::Verified(path.clone())
+        );
+        // The download is named after the URL, not the dest-filename.
+        let source =
+            json!({ "type": "file", "url": url, "dest-filename": "app.tar.gz", "sha512": SHA512 });
+        assert_eq!(
+            check_source(&top_dir, &source).unwrap(),
+            SourceCheck::Verified(path.clone())
+        );
+        let source =
+            json!({ "type": "archive", "url": url, "sha512": SHA512, "sha1": "0".repeat(40) });
+        assert_eq!(
+            check_source(&top_dir, &source).unwrap(),
+            SourceCheck::Mismatch(path.clone(), Checksum::Sha1)
+        );
+        let source = json!({ "type": "archive", "url": url, "sha1": SHA1 });
+        assert_eq!(
+            check_source(&top_dir, &source).unwrap(),
+            SourceCheck::Missing("app-1.0.tar.gz".to_string())
+        );
+        let source = json!({ "type": "git", "url": url });
+        assert_eq!(
+            check_source(&top_dir, &source).unwrap(),
+            SourceCheck::Skipped
+        );
+    }
+}
@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::manifest::desktop::check::{self, CheckContext};
+use crate::manifest::desktop::DesktopEntry;
+use crate::manifest::diagnostic;
+use crate::manifest::file as manifest_file;
+use crate::project::Project;
+use crate::{anyerror, error::Context, repo, Error, ErrorContext, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the desktop file from the manifest.
+    Generate(GenerateArgs),
+    /// Check the desktop file offline.
+    Check(CheckArgs),
+}
+
+#[derive(Parser)]
+struct GenerateArgs {
+    /// The name of the application. Default to the last part of the id.
+    #[arg(long)]
+    name: Option<String>,
+    /// Desktop category. Can be repeated. Default to Utility.
+    #[arg(long = "category")]
+    categories: Vec<String>,
+    /// Overwrite an existing desktop file.
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Parser)]
+struct CheckArgs {
+    /// The desktop file. Default to `<id>.desktop` in the project, or
+    /// the `rename-desktop-file` of the manifest.
+    file: Option<PathBuf>,
+}
+
+/// Generate `<id>.desktop` in the project and stage it.
+fn generate_desktop(args: GenerateArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest = manifest_file::load(&project.path.join(project.manifest_file()))?;
+
+    let id = manifest_file::id(&manifest).ok_or_else(|| anyerror!("No id in the manifest"))?;
+    let command = manifest
+        .get("command")
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| anyerror!("No command in the manifest"))?;
+    let name = args
+        .name
+        .as_deref()
+        .unwrap_or_else(|| id.rsplit_once('.').map(|(_, name)| name).unwrap_or(id));
+
+    let desktop_file = project.path.join(format!("{id}.desktop"));
+    if !args.force && desktop_file.exists() {
+        return Err(Error::AlreadyExist(ErrorContext::DesktopFile).into());
+    }
+    let desktop_file = DesktopEntry {
+        id,
+        name,
+        command,
+        categories: &args.categories,
+    }
+    .write(&project.path)?;
+    repo::add_path_to_repo(&project.repo()?, &desktop_file)?;
+
+    Ok(())
+}
+
+/// Check the desktop file and print the diagnostics.
+fn check_desktop(args: CheckArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest = manifest_file::load(&project.path.join(project.manifest_file()))?;
+    let context =
+        CheckContext::from_manifest(&manifest).ok_or_else(|| anyerror!("No id in the manifest"))?;
+
+    let file = args.file.unwrap_or_else(|| {
+        PathBuf::from(
+            context
+                .rename_desktop_file
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{}.desktop", context.id)),
+        )
+    });
+    let content = std::fs::read_to_string(&file).with_context(|| format!("Reading {file:?}"))?;
+    let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+
+    let diagnostics = check::check(&content, &file_name, &context);
+    diagnostic::report(&file, &diagnostics)
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Generate(args) => generate_desktop(args),
+        Command::Check(args) => check_desktop(args),
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use crate::manifest::flathub::{Flathub, Key};
+use crate::manifest::metainfo::rename;
+use crate::project::Project;
+use crate::{anyerror, error::Context, repo, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    /// The id of the application replacing this one.
+    #[arg(long)]
+    rebase: Option<String>,
+    /// The end of life message.
+    #[arg(long)]
+    message: Option<String>,
+    /// The metainfo of the replacing application, to add this
+    /// application to its `<replaces>` and `<provides>`.
+    #[arg(long, requires = "rebase")]
+    metainfo: Option<PathBuf>,
+    /// Don't commit the changes.
+    #[arg(long)]
+    no_commit: bool,
+}
+
+/// Add `old_id` to the metainfo of the replacing application and
+/// stage it. Commit it, unless `no_commit`, if it is in another
+/// repository than `project`. Return the path if it is staged in
+/// `project`.
+fn update_new_metainfo(
+    project: &Project,
+    metainfo: &Path,
+    old_id: &str,
+    no_commit: bool,
+) -> Result<Option<PathBuf>> {
+    let xml = std::fs::read_to_string(metainfo).with_context(|| format!("Reading {metainfo:?}"))?;
+    let xml = rename::add_renamed_from(&xml, old_id)?;
+    std::fs::write(metainfo, xml).with_context(|| format!("Writing {metainfo:?}"))?;
+
+    let path = metainfo.canonicalize()?;
+    let Ok(repo) = git2::Repository::discover(path.parent().unwrap_or(&path)) else {
+        return Ok(None);
+    };
+    let Some(workdir) = repo.workdir().and_then(|dir| dir.canonicalize().ok()) else {
+        return Ok(None);
+    };
+    repo::add_path_to_repo(&repo, &path)?;
+    if project.path.canonicalize().ok().as_ref() == Some(&workdir) {
+        let relative_path = path.strip_prefix(&workdir).unwrap_or(&path);
+        return Ok(Some(project.path.join(relative_path)));
+    }
+    if !no_commit {
+        repo::commit_paths(&repo, &[&path], &format!("Replace {old_id}"))?;
+    }
+
+    Ok(None)
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let id = project.id().to_string();
+    if args.rebase.as_deref() == Some(id.as_str()) {
+        return Err(anyerror!("The application can't be rebased on itself"));
+    }
+
+    let message = args.message.clone().unwrap_or_else(|| match args.rebase {
+        Some(ref new_id) => format!("This application has been renamed to {new_id}."),
+        None => "This application is no longer maintained.".to_string(),
+    });
+    let mut flathub = Flathub::load(&project.path)?;
+    flathub.set(Key::EndOfLife, &message)?;
+    match args.rebase {
+        Some(ref new_id) => flathub.set(Key::EndOfLifeRebase, new_id)?,
+        None => flathub.unset(Key::EndOfLifeRebase),
+    }
+    let repo = project.repo()?;
+    let flathub_file = flathub.generate(&project.path)?;
+    repo::add_path_to_repo(&repo, &flathub_file)?;
+    let mut paths = vec![flathub_file];
+
+    if let Some(ref metainfo) = args.metainfo {
+        paths.extend(update_new_metainfo(
+            &project,
+            metainfo,
+            &id,
+            args.no_commit,
+        )?);
+    }
+
+    if !args.no_commit {
+        let message = match args.rebase {
+            Some(ref new_id) => format!("End of life, renamed to {new_id}"),
+            None => "End of life".to_string(),
+        };
+        repo::commit_paths(&repo, &paths, &message)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use clap::{Parser, Subcommand};
+use serde_json::Value as JsonValue;
+
+use crate::manifest::flathub::{Flathub, Key};
+use crate::project::Project;
+use crate::{repo, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the value of KEY, or the whole file.
+    Get { key: Option<Key> },
+    /// Set KEY to VALUE. Arches are comma separated.
+    Set { key: Key, value: String },
+    /// Remove KEY.
+    Unset { key: Key },
+}
+
+/// Save `flathub` in the project and stage it.
+fn save(project: &Project, flathub: &Flathub) -> Result<()> {
+    let file = flathub.generate(&project.path)?;
+    repo::add_path_to_repo(&project.repo()?, file)
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let mut flathub = Flathub::load(&project.path)?;
+
+    match args.command {
+        Command::Get { key: None } => {
+            println!("{}", serde_json::to_string_pretty(&flathub)?);
+        }
+        Command::Get { key: Some(key) } => match flathub.get(key) {
+            Some(JsonValue::String(value)) => println!("{value}"),
+            Some(value) => println!("{value}"),
+            None => {}
+        },
+        Command::Set { key, value } => {
+            flathub.set(key, &value)?;
+            save(&project, &flathub)?;
+        }
+        Command::Unset { key } => {
+            flathub.unset(key);
+            flathub.validate()?;
+            save(&project, &flathub)?;
+        }
+    }
+
+    Ok(())
+}
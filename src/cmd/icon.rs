@@ -0,0 +1,98 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde_json::Value as JsonValue;
+
+use crate::manifest::diagnostic::Severity;
+use crate::manifest::file as manifest_file;
+use crate::manifest::flathub::Flathub;
+use crate::manifest::icon;
+use crate::project::Project;
+use crate::{anyerror, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check the icons in a build.
+    Check(CheckArgs),
+}
+
+#[derive(Parser)]
+struct CheckArgs {
+    /// The build directory of flatpak-builder.
+    build_dir: PathBuf,
+}
+
+/// Print the command to install the icon found in `project_dir`, the
+/// current directory. A SVG is preferred to a PNG.
+fn suggest_install(project_dir: &Path, manifest: &JsonValue, id: &str) -> Result<()> {
+    let icon = ["svg", "png"]
+        .iter()
+        .map(|ext| PathBuf::from(format!("{id}.{ext}")))
+        .find(|icon| project_dir.join(icon).is_file())
+        .unwrap_or_else(|| PathBuf::from("ICON.svg"));
+    let command = icon::install_command(&icon, id)?;
+
+    match manifest_file::main_module(manifest) {
+        Some(module) => println!("Add to the post-install of the module '{module}':"),
+        None => println!("Add to the post-install of the main module:"),
+    }
+    println!("    {command}");
+
+    Ok(())
+}
+
+/// Check the icons of the application in the build.
+fn check_icons(args: CheckArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest = manifest_file::load(&project.path.join(project.manifest_file()))?;
+    let id = manifest_file::id(&manifest).ok_or_else(|| anyerror!("No id in the manifest"))?;
+
+    if Flathub::load(&project.path)?.skip_icons_check == Some(true) {
+        println!("Icons check skipped by flathub.json.");
+        return Ok(());
+    }
+
+    let export_share = args.build_dir.join("export/share");
+    let share_dir = if export_share.is_dir() {
+        export_share
+    } else {
+        args.build_dir.join("files/share")
+    };
+    let check = icon::check_icons(&share_dir, id)?;
+    for (path, kind) in &check.icons {
+        println!("{}: {}", path.display(), kind.size_dir());
+    }
+    for (severity, message) in &check.problems {
+        println!("{severity}: {message}");
+    }
+    let errors = check
+        .problems
+        .iter()
+        .filter(|(severity, _)| *severity == Severity::Error)
+        .count();
+    if !check.has_usable_icon() {
+        suggest_install(&project.path, &manifest, id)?;
+    }
+    if errors > 0 {
+        return Err(anyerror!(format!("{errors} error(s) in the icons")));
+    }
+
+    Ok(())
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Check(args) => check_icons(args),
+    }
+}
@@ -1,24 +1,171 @@
-// SPDX-FileCopyrightText: 2023-2025 Hubert Figuière
+// SPDX-FileCopyrightText: 2023-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use clap::Parser;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
-use crate::manifest;
+use clap::{Parser, Subcommand};
+
+use crate::manifest::tui::{NonInteractiveUi, TerminalUi, Ui};
+use crate::manifest::{self, Answers, Template};
 use crate::project::Project;
-use crate::{Error, Result};
+use crate::Result;
 
 #[derive(Parser)]
 pub struct Args {
-    /// Command
-    command: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the manifest.
+    Create(Box<CreateArgs>),
 }
 
-fn create_manifest() -> Result<()> {
+#[derive(Parser)]
+struct CreateArgs {
+    /// Answer file (TOML or JSON). Command line options take precedence.
+    #[arg(long)]
+    answers: Option<PathBuf>,
+    /// Never prompt. Fail if an answer is missing.
+    #[arg(long)]
+    non_interactive: bool,
+    /// Package type: application, linux-audio-plugin or gimp-plugin.
+    #[arg(long)]
+    package_type: Option<String>,
+    /// Runtime: freedesktop, gnome, kde, qt6 or NAME//VERSION.
+    #[arg(long)]
+    runtime: Option<String>,
+    /// SDK: freedesktop, gnome, kde or qt6, with optional //VERSION.
+    #[arg(long)]
+    sdk: Option<String>,
+    /// SDK extension, NAME[//VERSION]. Can be repeated.
+    #[arg(long = "sdk-extension")]
+    sdk_extensions: Vec<String>,
+    /// Module: known module, shared-modules path or NAME[:BUILDSYSTEM].
+    /// Can be repeated.
+    #[arg(long = "module")]
+    modules: Vec<String>,
+    /// Build system for custom modules.
+    #[arg(long)]
+    buildsystem: Option<String>,
+    /// Template to instantiate.
+    #[arg(long)]
+    template: Option<String>,
+    /// Permission profile of an application: gui, audio or network.
+    /// Can be repeated.
+    #[arg(long = "finish-profile")]
+    finish_profiles: Vec<String>,
+    /// Command of an application.
+    #[arg(long)]
+    command: Option<String>,
+    /// Icon file of an application, SVG or PNG.
+    #[arg(long)]
+    icon: Option<String>,
+    /// Desktop category of an application. Can be repeated.
+    #[arg(long = "category")]
+    categories: Vec<String>,
+    /// Name of an application.
+    #[arg(long)]
+    name: Option<String>,
+    /// Summary of an application.
+    #[arg(long)]
+    summary: Option<String>,
+    /// Description of an application.
+    #[arg(long)]
+    description: Option<String>,
+    /// Developer id of an application.
+    #[arg(long)]
+    developer_id: Option<String>,
+    /// Developer name of an application.
+    #[arg(long)]
+    developer_name: Option<String>,
+    /// SPDX project license of an application.
+    #[arg(long)]
+    license: Option<String>,
+    /// URL of an application, TYPE=URL. Can be repeated.
+    #[arg(long = "url", value_parser = parse_key_value)]
+    urls: Vec<(String, String)>,
+    /// OARS content attribute, ID=VALUE. Can be repeated.
+    #[arg(long = "content-rating", value_parser = parse_key_value)]
+    content_rating: Vec<(String, String)>,
+    /// Brand color, light=#RRGGBB or dark=#RRGGBB. Can be repeated.
+    #[arg(long = "branding", value_parser = parse_key_value)]
+    branding: Vec<(String, String)>,
+    /// Screenshot image URL. Can be repeated.
+    #[arg(long = "screenshot")]
+    screenshots: Vec<String>,
+    /// Version of the first release.
+    #[arg(long)]
+    release: Option<String>,
+}
+
+/// Parse a `KEY=VALUE` argument.
+fn parse_key_value(s: &str) -> std::result::Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("'{s}' isn't KEY=VALUE"))
+}
+
+impl From<&CreateArgs> for Answers {
+    fn from(args: &CreateArgs) -> Answers {
+        Answers {
+            package_type: args.package_type.clone(),
+            runtime: args.runtime.clone(),
+            sdk: args.sdk.clone(),
+            sdk_extensions: (!args.sdk_extensions.is_empty()).then(|| args.sdk_extensions.clone()),
+            modules: (!args.modules.is_empty()).then(|| args.modules.clone()),
+            buildsystem: args.buildsystem.clone(),
+            template: args.template.clone(),
+            finish_profiles: (!args.finish_profiles.is_empty())
+                .then(|| args.finish_profiles.clone()),
+            command: args.command.clone(),
+            icon: args.icon.clone(),
+            categories: (!args.categories.is_empty()).then(|| args.categories.clone()),
+            name: args.name.clone(),
+            summary: args.summary.clone(),
+            description: args.description.clone(),
+            developer_id: args.developer_id.clone(),
+            developer_name: args.developer_name.clone(),
+            license: args.license.clone(),
+            urls: (!args.urls.is_empty()).then(|| args.urls.iter().cloned().collect()),
+            content_rating: (!args.content_rating.is_empty())
+                .then(|| args.content_rating.iter().cloned().collect()),
+            branding: (!args.branding.is_empty()).then(|| args.branding.iter().cloned().collect()),
+            screenshots: (!args.screenshots.is_empty()).then(|| args.screenshots.clone()),
+            release: args.release.clone(),
+            ..Answers::default()
+        }
+    }
+}
+
+fn create_manifest(args: CreateArgs) -> Result<()> {
     let current_dir = std::env::current_dir()?;
     let project = Project::open(current_dir)?;
 
-    let manifest = manifest::Manifest::prompt_with_id(Some(project.id())).ok_or(Error::Manifest)?;
+    let mut answers = match args.answers {
+        Some(ref path) => Answers::load(path)?,
+        None => Answers::default(),
+    };
+    answers.merge(Answers::from(&args));
+    let mut ui: Box<dyn Ui> = if !args.non_interactive && std::io::stdin().is_terminal() {
+        Box::new(TerminalUi::default())
+    } else {
+        Box::new(NonInteractiveUi)
+    };
+
+    let mut manifest = manifest::Manifest::prompt_with_id(
+        Some(project.id()),
+        Some(&project.path),
+        &answers,
+        ui.as_mut(),
+    )?;
+    if let Some(ref template) = answers.template {
+        let path = Template::find(template, &project.path)?;
+        manifest.apply_template(&path)?;
+    }
 
     let repo = project.repo()?;
     manifest.generate(&repo, project.path)?;
@@ -27,8 +174,7 @@ fn create_manifest() -> Result<()> {
 }
 
 pub fn run(args: Args) -> Result<()> {
-    match args.command.as_str() {
-        "create" => create_manifest(),
-        _ => Err(Error::InvalidArgument.into()),
+    match args.command {
+        Command::Create(args) => create_manifest(*args),
     }
 }
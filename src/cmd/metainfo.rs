@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::manifest::diagnostic;
+use crate::manifest::file as manifest_file;
+use crate::manifest::metainfo::check::{self, CheckContext};
+use crate::project::Project;
+use crate::{error::Context, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check the metainfo offline.
+    Check(CheckArgs),
+}
+
+#[derive(Parser)]
+struct CheckArgs {
+    /// The metainfo file. Default to `<id>.metainfo.xml` in the project.
+    file: Option<PathBuf>,
+}
+
+/// Check the metainfo and print the diagnostics.
+fn check_metainfo(args: CheckArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+
+    let file = args
+        .file
+        .unwrap_or_else(|| PathBuf::from(format!("{}.metainfo.xml", project.id())));
+    let xml = std::fs::read_to_string(&file).with_context(|| format!("Reading {file:?}"))?;
+    let manifest = manifest_file::load(&project.path.join(project.manifest_file()))?;
+
+    let diagnostics = check::check(
+        &xml,
+        &CheckContext {
+            manifest_id: manifest_file::id(&manifest),
+            project_dir: &project.path,
+        },
+    );
+
+    diagnostic::report(&file, &diagnostics)
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Check(args) => check_metainfo(args),
+    }
+}
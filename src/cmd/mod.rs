@@ -1,8 +1,17 @@
-// SPDX-FileCopyrightText: 2023 Hubert Figuière
+// SPDX-FileCopyrightText: 2023-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 pub(crate) mod cleanup;
 pub(crate) mod clone;
+pub(crate) mod desktop;
+pub(crate) mod eol;
+pub(crate) mod flathub_json;
+pub(crate) mod icon;
 pub(crate) mod init;
 pub(crate) mod manifest;
+pub(crate) mod metainfo;
+pub(crate) mod module;
+pub(crate) mod release;
+pub(crate) mod shared_modules;
+pub(crate) mod source;
@@ -0,0 +1,440 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::io::IsTerminal;
+use std::path::{Component, Path, PathBuf};
+
+use clap::{ArgGroup, Parser, Subcommand};
+use serde_json::{json, Value as JsonValue};
+
+use crate::flathub::{SHARED_MODULES, SHARED_MODULES_REPO};
+use crate::manifest::file as manifest_file;
+use crate::manifest::module::{Buildsystem, ModuleEntry};
+use crate::manifest::shared_modules;
+use crate::manifest::tree::{self, ModuleNode};
+use crate::manifest::tui::{TerminalUi, Ui};
+use crate::project::Project;
+use crate::{anyerror, repo, Error, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add a module to the manifest.
+    Add(AddArgs),
+    /// List the module tree of the manifest.
+    List(ListArgs),
+    /// Remove a module from the manifest.
+    Remove(RemoveArgs),
+    /// Move a module in the manifest.
+    Move(MoveArgs),
+    /// Move an inline module to its own file.
+    Extract(ExtractArgs),
+    /// Move the modules of an included file inline.
+    Inline(InlineArgs),
+    /// Search the modules of shared-modules.
+    Search(SearchArgs),
+}
+
+#[derive(Parser)]
+struct AddArgs {
+    /// The module: a known module, a shared-modules path or
+    /// NAME[:BUILDSYSTEM] for a custom module. Prompted if missing.
+    module: Option<String>,
+    /// The build system of a custom module. Default to autotools.
+    #[arg(long)]
+    buildsystem: Option<String>,
+    /// Add the module to the modules of the module PARENT, instead of
+    /// before the main module.
+    #[arg(long)]
+    parent: Option<String>,
+}
+
+#[derive(Parser)]
+struct ListArgs {
+    /// Output JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Parser)]
+struct RemoveArgs {
+    /// The name of the module, or the path of the included file.
+    name: String,
+    /// Remove the shared-modules submodule if it is no longer used,
+    /// without asking.
+    #[arg(long)]
+    drop_submodule: bool,
+}
+
+#[derive(Parser)]
+#[command(group(ArgGroup::new("position").required(true).args(["before", "after", "parent"])))]
+struct MoveArgs {
+    /// The name of the module, or the path of the included file.
+    name: String,
+    /// Move the module before the module BEFORE.
+    #[arg(long)]
+    before: Option<String>,
+    /// Move the module after the module AFTER.
+    #[arg(long)]
+    after: Option<String>,
+    /// Move the module at the end of the modules of the module PARENT.
+    #[arg(long)]
+    parent: Option<String>,
+}
+
+#[derive(Parser)]
+struct ExtractArgs {
+    /// The name of the module.
+    name: String,
+    /// The file, relative to the manifest. Default to `NAME.json`.
+    #[arg(long)]
+    file: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+struct InlineArgs {
+    /// The path of the included file, as in the manifest.
+    file: String,
+}
+
+#[derive(Parser)]
+struct SearchArgs {
+    /// The keyword to search in the name or the path. List all the
+    /// modules if missing.
+    keyword: Option<String>,
+    /// Update the cached copy of shared-modules first.
+    #[arg(long)]
+    update: bool,
+}
+
+/// Find the module `name` in `manifest`.
+fn find_module(manifest: &JsonValue, name: &str) -> Result<Vec<usize>> {
+    manifest_file::find_module(manifest, name)
+        .ok_or_else(|| anyerror!(format!("Module '{name}' not found")))
+}
+
+/// Add a module to the manifest, and the shared-modules submodule if
+/// needed.
+fn add_module(args: AddArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+
+    // A module of the shared-modules catalogue by name.
+    let shared_module = args.module.as_ref().and_then(|answer| {
+        shared_modules::catalogue(Some(&project.path))
+            .into_iter()
+            .find(|module| module.name == *answer)
+    });
+    let entry = match (&args.module, shared_module) {
+        (_, Some(module)) => ModuleEntry::SharedModule(module.path),
+        (Some(answer), None) => {
+            let buildsystem = match args.buildsystem {
+                Some(ref buildsystem) => {
+                    buildsystem.parse().map_err(|err: String| anyerror!(err))?
+                }
+                None => Buildsystem::default(),
+            };
+            ModuleEntry::from_answer(answer, &buildsystem).map_err(|err| anyerror!(err))?
+        }
+        (None, None) if std::io::stdin().is_terminal() => {
+            ModuleEntry::prompt_in(&mut TerminalUi::default(), Some(&project.path))
+                .ok_or_else(|| Error::MissingAnswer("module".to_string()))?
+        }
+        (None, None) => return Err(Error::MissingAnswer("module".to_string()).into()),
+    };
+    let value = JsonValue::from(&entry);
+    let name = manifest_file::module_name(&value)
+        .unwrap_or_default()
+        .to_string();
+    if manifest_file::find_module(&manifest, &name).is_some() {
+        return Err(anyerror!(format!(
+            "Module '{name}' already in the manifest"
+        )));
+    }
+
+    match args.parent {
+        Some(ref parent) => {
+            let indices = find_module(&manifest, parent)?;
+            manifest_file::module_at_mut(&mut manifest, &indices)
+                .and_then(manifest_file::modules_mut)
+                .ok_or_else(|| anyerror!(format!("Module '{parent}' is an included file")))?
+                .push(value);
+        }
+        None => {
+            let modules = manifest_file::modules_mut(&mut manifest)
+                .ok_or_else(|| anyerror!("Invalid manifest"))?;
+            let main = modules.len().saturating_sub(1);
+            modules.insert(main, value);
+        }
+    }
+    manifest_file::save(&manifest_path, &manifest)?;
+
+    let repo = project.repo()?;
+    repo::add_path_to_repo(&repo, &manifest_path)?;
+    if let ModuleEntry::SharedModule(ref path) = entry {
+        if !project.path.join(SHARED_MODULES).join(".git").exists() {
+            repo::add_submodule_to_repo(&repo, SHARED_MODULES_REPO, SHARED_MODULES)?;
+        }
+        if !project.path.join(path).exists() {
+            eprintln!("Warning: {path} doesn't exist in {SHARED_MODULES}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the `modules` at `depth` in the tree.
+fn print_modules(modules: &[ModuleNode], depth: usize) {
+    for module in modules {
+        let indent = "  ".repeat(depth);
+        if let Some(ref error) = module.error {
+            println!("{indent}{}: {error}", module.name);
+            continue;
+        }
+        let mut line = format!("{indent}{} [{}]", module.name, module.buildsystem);
+        for (idx, source) in module.sources.iter().enumerate() {
+            line.push_str(if idx == 0 { " " } else { ", " });
+            line.push_str(&source.type_);
+            if let Some(ref version) = source.version {
+                line.push_str(&format!(" {version}"));
+            }
+        }
+        if module.shared {
+            line.push_str(&format!(" ({SHARED_MODULES})"));
+        }
+        println!("{line}");
+        print_modules(&module.modules, depth + 1);
+    }
+}
+
+/// List the resolved module tree.
+fn list_modules(args: ListArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let manifest = manifest_file::load(&manifest_path)?;
+
+    let modules = tree::resolve(&manifest, manifest_path.parent().unwrap_or(&project.path));
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&modules)?);
+    } else {
+        print_modules(&modules, 0);
+    }
+
+    Ok(())
+}
+
+/// Remove a module from the manifest, and the shared-modules
+/// submodule if it is no longer used.
+fn remove_module(args: RemoveArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+
+    let indices = find_module(&manifest, &args.name)?;
+    let removed = manifest_file::remove_module(&mut manifest, &indices)
+        .ok_or_else(|| anyerror!(format!("Module '{}' not found", args.name)))?;
+    manifest_file::save(&manifest_path, &manifest)?;
+    let repo = project.repo()?;
+    repo::add_path_to_repo(&repo, &manifest_path)?;
+
+    let dir = manifest_path.parent().unwrap_or(&project.path);
+    let removed_shared =
+        tree::uses_shared_modules(&tree::resolve(&json!({ "modules": [removed] }), dir));
+    if removed_shared
+        && !tree::uses_shared_modules(&tree::resolve(&manifest, dir))
+        && project.path.join(SHARED_MODULES).join(".git").exists()
+    {
+        eprintln!("Warning: {SHARED_MODULES} is no longer used");
+        let drop_submodule = args.drop_submodule
+            || (std::io::stdin().is_terminal()
+                && TerminalUi::default()
+                    .confirm(&format!("Remove the {SHARED_MODULES} submodule?"), false)
+                    == Some(true));
+        if drop_submodule {
+            repo::remove_submodule(&repo, SHARED_MODULES)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Move a module in the module tree.
+fn move_module(args: MoveArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+
+    let target = args
+        .before
+        .as_deref()
+        .or(args.after.as_deref())
+        .or(args.parent.as_deref())
+        .unwrap_or_default();
+    let indices = find_module(&manifest, &args.name)?;
+    if find_module(&manifest, target)?.starts_with(&indices) {
+        return Err(anyerror!(format!(
+            "Can't move '{}' relative to itself",
+            args.name
+        )));
+    }
+    let module = manifest_file::remove_module(&mut manifest, &indices)
+        .ok_or_else(|| anyerror!(format!("Module '{}' not found", args.name)))?;
+
+    let target_indices = find_module(&manifest, target)?;
+    if args.parent.is_some() {
+        manifest_file::module_at_mut(&mut manifest, &target_indices)
+            .and_then(manifest_file::modules_mut)
+            .ok_or_else(|| anyerror!(format!("Module '{target}' is an included file")))?
+            .push(module);
+    } else {
+        let idx =
+            target_indices.last().copied().unwrap_or_default() + usize::from(args.after.is_some());
+        manifest_file::parent_modules_mut(&mut manifest, &target_indices)
+            .ok_or_else(|| anyerror!("Invalid manifest"))?
+            .insert(idx, module);
+    }
+    manifest_file::save(&manifest_path, &manifest)?;
+    repo::add_path_to_repo(&project.repo()?, &manifest_path)?;
+
+    Ok(())
+}
+
+/// Move the module to its own file and include it instead. Both
+/// files are staged.
+fn extract_module(args: ExtractArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or(&project.path);
+
+    let file = args
+        .file
+        .unwrap_or_else(|| PathBuf::from(format!("{}.json", args.name)));
+    if !file
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(anyerror!(format!(
+            "{file:?} must be in the directory of the manifest"
+        )));
+    }
+    if file.extension().is_none_or(|ext| ext != "json") {
+        return Err(anyerror!(format!("{file:?} must be a JSON file")));
+    }
+    let module_path = dir.join(&file);
+    if module_path.exists() {
+        return Err(anyerror!(format!("{file:?} already exists")));
+    }
+
+    let indices = find_module(&manifest, &args.name)?;
+    let entry = manifest_file::module_at_mut(&mut manifest, &indices)
+        .ok_or_else(|| anyerror!(format!("Module '{}' not found", args.name)))?;
+    if entry.is_string() {
+        return Err(anyerror!(format!("'{}' is an included file", args.name)));
+    }
+    let mut module = std::mem::replace(entry, JsonValue::from(file.to_string_lossy()));
+    manifest_file::rebase_module(
+        &mut module,
+        Path::new(""),
+        file.parent().unwrap_or(Path::new("")),
+    );
+
+    if let Some(parent) = module_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    manifest_file::save(&module_path, &module)?;
+    manifest_file::save(&manifest_path, &manifest)?;
+    let repo = project.repo()?;
+    repo::add_path_to_repo(&repo, &module_path)?;
+    repo::add_path_to_repo(&repo, &manifest_path)?;
+
+    Ok(())
+}
+
+/// Replace the included file with its modules. The file is removed,
+/// unless it is in shared-modules or still included.
+fn inline_module(args: InlineArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or(&project.path);
+
+    let indices = find_module(&manifest, &args.file)?;
+    if manifest_file::module_at_mut(&mut manifest, &indices).is_some_and(|entry| !entry.is_string())
+    {
+        return Err(anyerror!(format!("'{}' isn't an included file", args.file)));
+    }
+    let include = Path::new(&args.file);
+    let module_path = dir.join(include);
+    let mut modules = match manifest_file::load(&module_path)? {
+        JsonValue::Array(modules) => modules,
+        module => vec![module],
+    };
+    for module in &mut modules {
+        manifest_file::rebase_module(
+            module,
+            include.parent().unwrap_or(Path::new("")),
+            Path::new(""),
+        );
+    }
+    let idx = indices.last().copied().unwrap_or_default();
+    manifest_file::parent_modules_mut(&mut manifest, &indices)
+        .ok_or_else(|| anyerror!("Invalid manifest"))?
+        .splice(idx..=idx, modules);
+
+    let repo = project.repo()?;
+    manifest_file::save(&manifest_path, &manifest)?;
+    repo::add_path_to_repo(&repo, &manifest_path)?;
+    if !tree::is_shared(include) && manifest_file::find_module(&manifest, &args.file).is_none() {
+        repo::remove_path_from_repo(&repo, &module_path)?;
+        if let Some(parent) = module_path.parent().filter(|parent| *parent != dir) {
+            // Only if empty.
+            let _ = std::fs::remove_dir(parent);
+        }
+    }
+
+    Ok(())
+}
+
+/// Search the shared-modules catalogue.
+fn search_modules(args: SearchArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir).ok();
+    if args.update {
+        shared_modules::update_cache()?;
+    }
+
+    let checkout = shared_modules::checkout(project.as_ref().map(|project| project.path.as_path()))
+        .ok_or_else(|| anyerror!("No shared-modules checkout. Use --update to fetch it"))?;
+    let catalogue = shared_modules::index(&checkout);
+    for module in shared_modules::search(&catalogue, args.keyword.as_deref().unwrap_or_default()) {
+        println!("{module}");
+    }
+
+    Ok(())
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Add(args) => add_module(args),
+        Command::List(args) => list_modules(args),
+        Command::Remove(args) => remove_module(args),
+        Command::Move(args) => move_module(args),
+        Command::Extract(args) => extract_module(args),
+        Command::Inline(args) => inline_module(args),
+        Command::Search(args) => search_modules(args),
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::manifest::metainfo::release::{self, NewRelease};
+use crate::manifest::metainfo::{date_from_timestamp, today};
+use crate::project::Project;
+use crate::{anyerror, error::Context, repo, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add a release to the metainfo.
+    Add(AddArgs),
+}
+
+#[derive(Parser)]
+struct AddArgs {
+    /// The version of the release.
+    version: String,
+    /// The date of the release, YYYY-MM-DD. Default to today, or the
+    /// date of the tag.
+    #[arg(long)]
+    date: Option<String>,
+    /// The description of the release.
+    #[arg(long, group = "release_description")]
+    description: Option<String>,
+    /// Read the description of the release from FILE.
+    #[arg(long, value_name = "FILE", group = "release_description")]
+    changelog: Option<PathBuf>,
+    /// Use the message of the annotated git TAG as the description.
+    #[arg(long, value_name = "TAG", group = "release_description")]
+    from_tag: Option<String>,
+    /// The git repository with the tag. Default to the project.
+    #[arg(long, requires = "from_tag")]
+    tag_repo: Option<PathBuf>,
+    /// The metainfo file. Default to `<id>.metainfo.xml` in the project.
+    #[arg(long)]
+    file: Option<PathBuf>,
+    /// Commit the metainfo, with any other staged change.
+    #[arg(long)]
+    commit: bool,
+}
+
+/// Return the message and the date of the annotated `tag` in the
+/// repository at `path`.
+fn tag_message(path: &PathBuf, tag: &str) -> Result<(String, String)> {
+    let repo = git2::Repository::open(path)?;
+    let object = repo.revparse_single(&format!("refs/tags/{tag}"))?;
+    let tag = object
+        .as_tag()
+        .ok_or_else(|| anyerror!(format!("Tag {tag} isn't annotated")))?;
+    let message = tag.message()?.unwrap_or_default().trim().to_string();
+    let date = tag
+        .tagger()
+        .map(|tagger| date_from_timestamp(tagger.when().seconds()))
+        .unwrap_or_else(today);
+
+    Ok((message, date))
+}
+
+fn add_release(args: AddArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+
+    let (description, tag_date) = if let Some(ref changelog) = args.changelog {
+        let description =
+            std::fs::read_to_string(changelog).with_context(|| format!("Reading {changelog:?}"))?;
+        (Some(description), None)
+    } else if let Some(ref tag) = args.from_tag {
+        let path = args.tag_repo.as_ref().unwrap_or(&project.path);
+        let (message, date) = tag_message(path, tag)?;
+        (Some(message), Some(date))
+    } else {
+        (args.description, None)
+    };
+    let date = args.date.or(tag_date).unwrap_or_else(today);
+
+    let file = args
+        .file
+        .unwrap_or_else(|| project.path.join(format!("{}.metainfo.xml", project.id())));
+    let xml = std::fs::read_to_string(&file).with_context(|| format!("Reading {file:?}"))?;
+    let xml = release::add_release(
+        &xml,
+        &NewRelease {
+            version: args.version.clone(),
+            date,
+            description,
+        },
+    )?;
+    std::fs::write(&file, xml).with_context(|| format!("Writing {file:?}"))?;
+
+    if args.commit {
+        let file = if file.is_absolute() {
+            file
+        } else {
+            current_dir.join(file)
+        };
+        commit_release(&project.repo()?, &file, &args.version)?;
+    }
+
+    Ok(())
+}
+
+/// Stage the metainfo `file` and commit it with the other staged
+/// changes, like a source update, as "Release VERSION".
+fn commit_release(repo: &git2::Repository, file: &Path, version: &str) -> Result<git2::Oid> {
+    repo::add_path_to_repo(repo, file)?;
+    repo::commit(repo, &format!("Release {version}"))
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Add(args) => add_release(args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.org").unwrap();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        // A staged source update.
+        std::fs::write(workdir.join("org.example.App.json"), "{}").unwrap();
+        repo::add_path_to_repo(&repo, workdir.join("org.example.App.json")).unwrap();
+        let metainfo = workdir.join("org.example.App.metainfo.xml");
+        std::fs::write(&metainfo, "<component/>").unwrap();
+
+        let commit = commit_release(&repo, &metainfo, "1.0").unwrap();
+        let commit = repo.find_commit(commit).unwrap();
+        assert_eq!(commit.message().unwrap(), "Release 1.0");
+        let tree = commit.tree().unwrap();
+        assert!(tree.get_name("org.example.App.json").is_some());
+        assert!(tree.get_name("org.example.App.metainfo.xml").is_some());
+    }
+}
@@ -0,0 +1,232 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::flathub::SHARED_MODULES;
+use crate::manifest::file as manifest_file;
+use crate::manifest::shared_modules::{self, Drift};
+use crate::manifest::tree;
+use crate::project::Project;
+use crate::{anyerror, error::Context, repo, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show how far shared-modules is behind its remote branch.
+    Status(StatusArgs),
+    /// Update shared-modules to its remote branch and stage it.
+    Update,
+    /// Copy a shared module to the project and include it from there.
+    Vendor(VendorArgs),
+}
+
+#[derive(Parser)]
+struct StatusArgs {
+    /// Don't fetch the remote.
+    #[arg(long)]
+    no_fetch: bool,
+}
+
+#[derive(Parser)]
+struct VendorArgs {
+    /// The module file, as included in the manifest, or relative to
+    /// shared-modules.
+    path: PathBuf,
+}
+
+/// The short form of `oid`.
+fn short_id(oid: git2::Oid) -> String {
+    oid.to_string().chars().take(7).collect()
+}
+
+/// Return the shared-modules files included by the manifest, relative
+/// to the submodule.
+fn included_files(project: &Project) -> Result<Vec<PathBuf>> {
+    let manifest_path = project.path.join(project.manifest_file());
+    let manifest = manifest_file::load(&manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or(&project.path);
+
+    Ok(tree::shared_files(&tree::resolve(&manifest, dir))
+        .iter()
+        .filter_map(|file| file.strip_prefix(SHARED_MODULES).ok())
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// Print the changed files of `drift`.
+fn print_changed(drift: &Drift) {
+    if !drift.changed.is_empty() {
+        println!("Changed files of the included modules:");
+        for file in &drift.changed {
+            println!("    {file}");
+        }
+    }
+}
+
+/// Open the shared-modules submodule of `project` and compute its
+/// drift. Return the pinned and remote commits with it.
+fn submodule_drift(
+    project: &Project,
+    repo: &git2::Repository,
+    fetch: bool,
+) -> Result<(git2::Oid, git2::Oid, Drift)> {
+    let submodule = repo
+        .find_submodule(SHARED_MODULES)
+        .map_err(|_| anyerror!("No shared-modules submodule"))?;
+    let pinned = submodule
+        .index_id()
+        .or(submodule.head_id())
+        .ok_or_else(|| anyerror!("shared-modules isn't pinned"))?;
+    let sub_repo = submodule.open()?;
+    let branch = submodule.branch()?;
+    let remote = if fetch {
+        shared_modules::fetch_remote_head(&sub_repo, branch)?
+    } else {
+        shared_modules::remote_head(&sub_repo, branch)?
+    };
+    let drift = shared_modules::drift(&sub_repo, pinned, remote, &included_files(project)?)?;
+
+    Ok((pinned, remote, drift))
+}
+
+/// Print how far shared-modules is behind.
+fn status(args: StatusArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let repo = project.repo()?;
+
+    let (pinned, remote, drift) = submodule_drift(&project, &repo, !args.no_fetch)?;
+    if drift.behind == 0 {
+        println!("{SHARED_MODULES} is up to date at {}", short_id(pinned));
+        return Ok(());
+    }
+    println!(
+        "{SHARED_MODULES} at {} is {} commit(s) behind {}",
+        short_id(pinned),
+        drift.behind,
+        short_id(remote)
+    );
+    print_changed(&drift);
+
+    Ok(())
+}
+
+/// Fast-forward shared-modules to its remote branch and stage the
+/// submodule.
+fn update() -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let repo = project.repo()?;
+
+    let (pinned, remote, drift) = submodule_drift(&project, &repo, true)?;
+    if drift.behind == 0 {
+        println!("{SHARED_MODULES} is up to date at {}", short_id(pinned));
+        return Ok(());
+    }
+    let mut submodule = repo.find_submodule(SHARED_MODULES)?;
+    let sub_repo = submodule.open()?;
+    if !sub_repo.graph_descendant_of(remote, pinned)? {
+        return Err(anyerror!(format!(
+            "{SHARED_MODULES} can't be fast-forwarded to {}",
+            short_id(remote)
+        )));
+    }
+    if sub_repo
+        .statuses(None)?
+        .iter()
+        .any(|entry| !entry.status().is_ignored())
+    {
+        return Err(anyerror!(format!("{SHARED_MODULES} has local changes")));
+    }
+
+    let commit = sub_repo.find_commit(remote)?;
+    sub_repo.checkout_tree(commit.as_object(), None)?;
+    sub_repo.set_head_detached(remote)?;
+    submodule.add_to_index(true)?;
+    println!(
+        "Updated {SHARED_MODULES} from {} to {}, {} commit(s)",
+        short_id(pinned),
+        short_id(remote),
+        drift.behind
+    );
+    print_changed(&drift);
+
+    Ok(())
+}
+
+/// Copy the shared module and its files to the project, with the same
+/// layout, and include it instead. Remove the submodule if no longer
+/// used.
+fn vendor(args: VendorArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or(&project.path);
+
+    let path = args
+        .path
+        .strip_prefix(SHARED_MODULES)
+        .unwrap_or(&args.path)
+        .to_path_buf();
+    let include = Path::new(SHARED_MODULES).join(&path);
+    let include = include.to_string_lossy();
+    let vendored = path.to_string_lossy();
+    if manifest_file::find_module(&manifest, &include).is_none() {
+        return Err(anyerror!(format!(
+            "{include} isn't included by the manifest"
+        )));
+    }
+
+    let checkout = dir.join(SHARED_MODULES);
+    let files = shared_modules::module_files(&checkout, &path)?;
+    if let Some(file) = files.iter().find(|file| dir.join(file).exists()) {
+        return Err(anyerror!(format!("{file:?} already exists")));
+    }
+    let repo = project.repo()?;
+    for file in &files {
+        let source = checkout.join(file);
+        if !source.is_file() {
+            eprintln!("Warning: {file:?} isn't a file, not copied");
+            continue;
+        }
+        let dest = dir.join(file);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, &dest).with_context(|| format!("Copying {source:?}"))?;
+        repo::add_path_to_repo(&repo, &dest)?;
+    }
+
+    while let Some(entry) = manifest_file::find_module(&manifest, &include)
+        .and_then(|indices| manifest_file::module_at_mut(&mut manifest, &indices))
+    {
+        *entry = vendored.as_ref().into();
+    }
+    manifest_file::save(&manifest_path, &manifest)?;
+    repo::add_path_to_repo(&repo, &manifest_path)?;
+
+    if !tree::uses_shared_modules(&tree::resolve(&manifest, dir)) {
+        println!("{SHARED_MODULES} is no longer used, removing it");
+        repo::remove_submodule(&repo, SHARED_MODULES)?;
+    }
+
+    Ok(())
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Status(args) => status(args),
+        Command::Update => update(),
+        Command::Vendor(args) => vendor(args),
+    }
+}
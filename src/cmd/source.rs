@@ -0,0 +1,456 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde_json::Value as JsonValue;
+
+use crate::download;
+use crate::manifest::file as manifest_file;
+use crate::manifest::module::{Source, SourceType};
+use crate::manifest::pin;
+use crate::manifest::tree;
+use crate::project::Project;
+use crate::{anyerror, repo, Result};
+
+#[derive(Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Download a source and add it to a module.
+    Add(AddArgs),
+    /// Pin the git sources to the commit of their tag or branch.
+    Pin(PinArgs),
+    /// Update the source of a module to a new version.
+    Update(UpdateArgs),
+}
+
+#[derive(Parser)]
+struct AddArgs {
+    /// The URL of the source. `file://` URLs are supported.
+    url: String,
+    /// The module to add the source to. Default to the main module.
+    #[arg(long)]
+    module: Option<String>,
+    /// The type of the source: archive or file. Guessed from the file
+    /// name.
+    #[arg(long = "type")]
+    type_: Option<String>,
+    /// The file name of the source in the build directory. Default to
+    /// the last part of the URL.
+    #[arg(long)]
+    dest_filename: Option<String>,
+}
+
+#[derive(Parser)]
+struct PinArgs {
+    /// Only check, and fail if a git source isn't pinned or its commit
+    /// doesn't match.
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Parser)]
+struct UpdateArgs {
+    /// The new version.
+    version: String,
+    /// The module. Default to the main module.
+    #[arg(long)]
+    module: Option<String>,
+    /// The new URL of an archive or file source. Default to the URL
+    /// with the new version.
+    #[arg(long, conflicts_with = "tag")]
+    url: Option<String>,
+    /// The new tag of a git source. Default to the tag with the new
+    /// version.
+    #[arg(long)]
+    tag: Option<String>,
+    /// Commit the change as "Update MODULE to VERSION".
+    #[arg(long)]
+    commit: bool,
+}
+
+/// Return the sources of the inline module `name` in `manifest`.
+fn module_sources<'a>(manifest: &'a mut JsonValue, name: &str) -> Result<&'a mut Vec<JsonValue>> {
+    let indices = manifest_file::find_module(manifest, name)
+        .ok_or_else(|| anyerror!(format!("Module '{name}' not found")))?;
+
+    manifest_file::module_at_mut(manifest, &indices)
+        .and_then(JsonValue::as_object_mut)
+        .ok_or_else(|| anyerror!(format!("'{name}' is an included file")))?
+        .entry("sources")
+        .or_insert_with(|| JsonValue::Array(vec![]))
+        .as_array_mut()
+        .ok_or_else(|| anyerror!(format!("Invalid sources in '{name}'")))
+}
+
+/// Download the source, and add it with its checksum to the module.
+fn add_source(args: AddArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+
+    let module = args
+        .module
+        .as_deref()
+        .or_else(|| manifest_file::main_module(&manifest))
+        .ok_or_else(|| anyerror!("No module in the manifest"))?
+        .to_string();
+    let sources = module_sources(&mut manifest, &module)?;
+    if sources
+        .iter()
+        .any(|source| source.get("url").and_then(JsonValue::as_str) == Some(&args.url))
+    {
+        return Err(anyerror!(format!("{} already in '{module}'", args.url)));
+    }
+
+    let download = download::download(&project.path, &args.url)?;
+    let name = match args.dest_filename {
+        Some(ref name) => name.into(),
+        None => download
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy(),
+    };
+    let type_ = match args.type_.as_deref() {
+        Some("archive") => SourceType::Archive,
+        Some("file") => SourceType::File,
+        Some(type_) => {
+            return Err(anyerror!(format!(
+                "Can't add a {type_} source, only archive or file"
+            )))
+        }
+        None if tree::is_archive(&name) => SourceType::Archive,
+        None => SourceType::File,
+    };
+    let source = Source {
+        type_,
+        dest_filename: args.dest_filename.clone(),
+        ..Source::archive(&args.url, &download.sha256)
+    };
+    source.validate().map_err(|err| anyerror!(err))?;
+    sources.push(serde_json::to_value(&source)?);
+
+    manifest_file::save(&manifest_path, &manifest)?;
+    repo::add_path_to_repo(&project.repo()?, &manifest_path)?;
+    println!(
+        "Added {type_} {name} ({} bytes, sha256 {}) to '{module}'",
+        download.size, download.sha256
+    );
+
+    Ok(())
+}
+
+/// Return the paths of the module files included by `value`,
+/// recursively, relative to `dir`.
+fn included_files(value: &JsonValue, dir: &Path, files: &mut Vec<PathBuf>) {
+    for module in manifest_file::modules(value) {
+        match module {
+            JsonValue::String(path) => {
+                let path = dir.join(path);
+                if files.contains(&path) || tree::is_shared(&path) {
+                    continue;
+                }
+                files.push(path.clone());
+                if let Ok(included) = manifest_file::load(&path) {
+                    let dir = path.parent().unwrap_or(dir);
+                    if included.is_array() {
+                        for module in included.as_array().into_iter().flatten() {
+                            included_files(module, dir, files);
+                        }
+                    } else {
+                        included_files(&included, dir, files);
+                    }
+                }
+            }
+            module => included_files(module, dir, files),
+        }
+    }
+}
+
+/// Pin the git sources of the manifest and the included module files,
+/// shared-modules excepted.
+fn pin_sources(args: PinArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let manifest = manifest_file::load(&manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or(&project.path);
+    let mut files = vec![manifest_path.clone()];
+    included_files(&manifest, dir, &mut files);
+
+    let mut remotes = HashMap::<String, pin::Heads>::new();
+    let mut ls_remote = |url: &str| -> Result<pin::Heads> {
+        if let Some(heads) = remotes.get(url) {
+            return Ok(heads.clone());
+        }
+        let heads = repo::ls_remote(url)?;
+        remotes.insert(url.to_string(), heads.clone());
+        Ok(heads)
+    };
+    let repo = project.repo()?;
+    let mut errors = 0;
+    for file in &files {
+        let Ok(mut value) = manifest_file::load(file) else {
+            eprintln!("Warning: can't load {file:?}, skipped");
+            continue;
+        };
+        let reports = pin::pin_sources(&mut value, &mut ls_remote, args.check)?;
+        let name = file.strip_prefix(dir).unwrap_or(file);
+        for report in &reports {
+            println!("{}: {report}", name.display());
+        }
+        errors += reports
+            .iter()
+            .filter(|report| report.status.is_error())
+            .count();
+        if reports
+            .iter()
+            .any(|report| matches!(report.status, pin::Status::Pinned(_)))
+        {
+            manifest_file::save(file, &value)?;
+            repo::add_path_to_repo(&repo, file)?;
+        }
+    }
+
+    if errors > 0 {
+        return Err(anyerror!(format!(
+            "{errors} git source(s) not pinned or not matching"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Update the git `source` to the tag `tag`, or the tag with
+/// `version`, and its commit. Return the tag.
+fn update_git_source(source: &mut JsonValue, version: &str, tag: Option<&str>) -> Result<String> {
+    let tag = match tag {
+        Some(tag) => tag.to_string(),
+        None => {
+            let old_tag = source
+                .get("tag")
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| anyerror!("No tag in the git source, use --tag"))?;
+            let old_version = tree::source_summary(source)
+                .version
+                .ok_or_else(|| anyerror!(format!("No version in the tag {old_tag}")))?;
+            // The version is dotted, the tag may use `_`, like `release-2_1_0`.
+            let tag = if old_tag.contains(&old_version) {
+                tree::substitute_version(old_tag, &old_version, version)
+            } else {
+                let underscored = |version: &str| version.replace('.', "_");
+                tree::substitute_version(old_tag, &underscored(&old_version), &underscored(version))
+            };
+            if tag == old_tag {
+                return Err(anyerror!(format!(
+                    "The tag {old_tag} is unchanged for {version}, use --tag"
+                )));
+            }
+            tag
+        }
+    };
+    let url = source
+        .get("url")
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default();
+    let commit = repo::tag_commit(&repo::ls_remote(url)?, &tag)
+        .ok_or_else(|| anyerror!(format!("Tag {tag} not found in {url}")))?;
+
+    if let Some(source) = source.as_object_mut() {
+        source.shift_remove("branch");
+        source.insert("tag".to_string(), tag.clone().into());
+        source.insert("commit".to_string(), commit.to_string().into());
+    }
+
+    Ok(tag)
+}
+
+/// Update the archive or file `source` to `url`, or the URL with
+/// `version`, downloaded and hashed. Return the download.
+fn update_download_source(
+    top_dir: &Path,
+    source: &mut JsonValue,
+    version: &str,
+    url: Option<&str>,
+) -> Result<download::Download> {
+    let old_url = source
+        .get("url")
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default();
+    let url = match url {
+        Some(url) => url.to_string(),
+        None => {
+            let old_version = tree::source_summary(source)
+                .version
+                .ok_or_else(|| anyerror!(format!("No version in {old_url}, use --url")))?;
+            tree::substitute_version(old_url, &old_version, version)
+        }
+    };
+    if url == old_url {
+        return Err(anyerror!(format!("The source is already {url}")));
+    }
+    let download = download::download(top_dir, &url)?;
+
+    if let Some(source) = source.as_object_mut() {
+        for checksum in ["md5", "sha1", "sha512"] {
+            source.shift_remove(checksum);
+        }
+        source.insert("url".to_string(), url.into());
+        source.insert("sha256".to_string(), download.sha256.clone().into());
+    }
+
+    Ok(download)
+}
+
+/// Update the first archive, file or git source of the module to a new
+/// version.
+fn update_source(args: UpdateArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let project = Project::open(&current_dir)?;
+    let manifest_path = project.path.join(project.manifest_file());
+    let mut manifest = manifest_file::load(&manifest_path)?;
+
+    let module = args
+        .module
+        .as_deref()
+        .or_else(|| manifest_file::main_module(&manifest))
+        .ok_or_else(|| anyerror!("No module in the manifest"))?
+        .to_string();
+    let source = module_sources(&mut manifest, &module)?
+        .iter_mut()
+        .find(|source| {
+            source.get("url").is_some()
+                && matches!(
+                    source
+                        .get("type")
+                        .and_then(JsonValue::as_str)
+                        .unwrap_or("archive"),
+                    "archive" | "file" | "git"
+                )
+        })
+        .ok_or_else(|| anyerror!(format!("No archive, file or git source in '{module}'")))?;
+
+    let is_git = source.get("type").and_then(JsonValue::as_str) == Some("git");
+    if is_git && args.url.is_some() {
+        return Err(anyerror!(format!(
+            "The source of '{module}' is git, use --tag instead of --url"
+        )));
+    }
+    if !is_git && args.tag.is_some() {
+        return Err(anyerror!(format!(
+            "The source of '{module}' isn't git, use --url instead of --tag"
+        )));
+    }
+    if is_git {
+        let tag = update_git_source(source, &args.version, args.tag.as_deref())?;
+        println!("Updated '{module}' to {tag}");
+    } else {
+        let download =
+            update_download_source(&project.path, source, &args.version, args.url.as_deref())?;
+        println!(
+            "Updated '{module}' to {} ({} bytes, sha256 {})",
+            args.version, download.size, download.sha256
+        );
+    }
+
+    manifest_file::save(&manifest_path, &manifest)?;
+    let repo = project.repo()?;
+    repo::add_path_to_repo(&repo, &manifest_path)?;
+    if args.commit {
+        repo::commit_paths(
+            &repo,
+            &[&manifest_path],
+            &format!("Update {module} to {}", args.version),
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Command::Add(args) => add_source(args),
+        Command::Pin(args) => pin_sources(args),
+        Command::Update(args) => update_source(args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use url::Url;
+
+    use super::*;
+
+    #[test]
+    fn test_update_git_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.org").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let object = repo.find_object(commit, None).unwrap();
+        for tag in ["release-2_1_0", "release-2_2_0", "v1.1"] {
+            repo.tag_lightweight(tag, &object, false).unwrap();
+        }
+        let url = dir.path().to_string_lossy();
+        let mut source = json!({ "type": "git", "url": url, "tag": "release-2_1_0" });
+
+        let tag = update_git_source(&mut source, "2.2.0", None).unwrap();
+        assert_eq!(tag, "release-2_2_0");
+        assert_eq!(
+            source,
+            json!({ "type": "git", "url": url, "tag": "release-2_2_0", "commit": commit.to_string() })
+        );
+        // Same version.
+        assert!(update_git_source(&mut source, "2.2.0", None).is_err());
+        // Not in the remote.
+        assert!(update_git_source(&mut source, "2.3.0", None).is_err());
+
+        let tag = update_git_source(&mut source, "1.1", Some("v1.1")).unwrap();
+        assert_eq!(tag, "v1.1");
+        assert_eq!(source["tag"], "v1.1");
+    }
+
+    #[test]
+    fn test_update_download_source() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["app-1.0.tar.gz", "app-1.1.tar.gz"] {
+            std::fs::write(dir.path().join(file), "hello").unwrap();
+        }
+        let url = |file: &str| {
+            Url::from_file_path(dir.path().join(file))
+                .unwrap()
+                .to_string()
+        };
+        let mut source = json!({ "type": "archive", "url": url("app-1.0.tar.gz"), "sha1": "0" });
+
+        let download = update_download_source(dir.path(), &mut source, "1.1", None).unwrap();
+        assert_eq!(
+            source,
+            json!({ "type": "archive", "url": url("app-1.1.tar.gz"), "sha256": download.sha256 })
+        );
+        // Same version.
+        assert!(update_download_source(dir.path(), &mut source, "1.1", None).is_err());
+        // Not found.
+        assert!(update_download_source(dir.path(), &mut source, "1.2", None).is_err());
+
+        update_download_source(dir.path(), &mut source, "1.0", Some(&url("app-1.0.tar.gz")))
+            .unwrap();
+        assert_eq!(source["url"], url("app-1.0.tar.gz"));
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Download sources to the flatpak-builder downloads.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{anyerror, builder, error::Context, Result};
+
+/// A downloaded file.
+pub(crate) struct Download {
+    /// The path in the downloads.
+    pub path: PathBuf,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Return the file name of the download of `url`, the last segment of
+/// the path. flatpak-builder ignores the `dest-filename` of the source
+/// for the download.
+pub(crate) fn file_name(url: &Url) -> Option<String> {
+    url.path_segments()?
+        .rfind(|segment| !segment.is_empty())
+        .map(str::to_string)
+}
+
+/// The kind of a source checksum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Checksum {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Checksum {
+    /// All the kinds, by order of preference.
+    pub const ALL: [Checksum; 3] = [Checksum::Sha256, Checksum::Sha512, Checksum::Sha1];
+
+    /// The source property of the checksum.
+    pub fn key(self) -> &'static str {
+        match self {
+            Checksum::Sha1 => "sha1",
+            Checksum::Sha256 => "sha256",
+            Checksum::Sha512 => "sha512",
+        }
+    }
+
+    /// Return the kind of the hex `checksum` from its length.
+    pub fn from_hex(checksum: &str) -> Option<Checksum> {
+        if !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        match checksum.len() {
+            40 => Some(Checksum::Sha1),
+            64 => Some(Checksum::Sha256),
+            128 => Some(Checksum::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Return the hex checksum of the file at `path`.
+    pub fn hash_file(self, path: &Path) -> Result<String> {
+        fn hash<D: Digest + Write>(file: &mut std::fs::File) -> Result<String> {
+            let mut hasher = D::new();
+            std::io::copy(file, &mut hasher)?;
+            Ok(hasher
+                .finalize()
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect())
+        }
+
+        let mut file = std::fs::File::open(path).with_context(|| format!("Opening {path:?}"))?;
+        match self {
+            Checksum::Sha1 => hash::<sha1::Sha1>(&mut file),
+            Checksum::Sha256 => hash::<Sha256>(&mut file),
+            Checksum::Sha512 => hash::<sha2::Sha512>(&mut file),
+        }
+    }
+}
+
+/// Copy `reader` to `writer` and return the SHA-256 and the size.
+fn copy_hashed(reader: &mut dyn Read, writer: &mut dyn Write) -> Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut size = 0_u64;
+    loop {
+        let len = reader.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+        writer.write_all(&buffer[..len])?;
+        size += len as u64;
+    }
+
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+/// Download `url` to the downloads of the project in `top_dir`, as
+/// `<sha256>/<name>` with the file name of the URL, like
+/// flatpak-builder. `file://` URLs are supported.
+pub(crate) fn download(top_dir: &Path, url: &str) -> Result<Download> {
+    let url = Url::parse(url)?;
+    let name = file_name(&url).ok_or_else(|| anyerror!(format!("No file name in {url}")))?;
+    let mut reader: Box<dyn Read> = match url.scheme() {
+        "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyerror!(format!("Invalid file URL {url}")))?;
+            Box::new(std::fs::File::open(&path).with_context(|| format!("Opening {path:?}"))?)
+        }
+        "http" | "https" => Box::new(ureq::get(url.as_str()).call()?.into_body().into_reader()),
+        scheme => return Err(anyerror!(format!("Unsupported URL scheme {scheme}"))),
+    };
+
+    let downloads_dir = top_dir.join(builder::downloads_dir());
+    std::fs::create_dir_all(&downloads_dir)?;
+    let partial = downloads_dir.join(format!(".{name}.part"));
+    let mut file =
+        std::fs::File::create(&partial).with_context(|| format!("Creating {partial:?}"))?;
+    let result = copy_hashed(&mut reader, &mut file);
+    drop(file);
+    let (sha256, size) = match result {
+        Ok(result) => result,
+        Err(err) => {
+            let _ = std::fs::remove_file(&partial);
+            return Err(err);
+        }
+    };
+
+    let path = builder::download_path(top_dir, &name, &sha256);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&partial, &path).with_context(|| format!("Moving to {path:?}"))?;
+
+    Ok(Download { path, sha256, size })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("app-1.0.tar.gz");
+        std::fs::write(&source, "hello").unwrap();
+        let url = Url::from_file_path(&source).unwrap();
+
+        let download = download(dir.path(), url.as_str()).unwrap();
+        let sha256 = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert_eq!(download.sha256, sha256);
+        assert_eq!(download.size, 5);
+        assert_eq!(
+            download.path,
+            dir.path()
+                .join(".flatpak-builder/downloads")
+                .join(sha256)
+                .join("app-1.0.tar.gz")
+        );
+        assert_eq!(std::fs::read(&download.path).unwrap(), b"hello");
+
+        assert_eq!(Checksum::from_hex(sha256), Some(Checksum::Sha256));
+        assert_eq!(Checksum::from_hex("not-a-checksum"), None);
+        assert_eq!(Checksum::Sha256.hash_file(&download.path).unwrap(), sha256);
+        assert_eq!(
+            Checksum::Sha1.hash_file(&download.path).unwrap(),
+            "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"
+        );
+    }
+}
@@ -11,6 +11,8 @@ pub enum ErrorContext {
     Project,
     /// Error relate to git repository
     Repository,
+    /// Error relate to the desktop file
+    DesktopFile,
 }
 
 impl std::fmt::Display for ErrorContext {
@@ -22,6 +24,7 @@ impl std::fmt::Display for ErrorContext {
                 Self::Directory => "Directory",
                 Self::Project => "Project",
                 Self::Repository => "Repository",
+                Self::DesktopFile => "Desktop file",
             }
         )
     }
@@ -109,6 +112,8 @@ pub enum Error {
     Manifest,
     #[error("Not implemented")]
     NotImplemented,
+    #[error("No answer for {0} in non-interactive mode")]
+    MissingAnswer(String),
     #[error("IO error {0}")]
     Io(#[from] std::io::Error),
     #[error("Git error {0}")]
@@ -121,6 +126,10 @@ pub enum Error {
     SerdeJson(#[from] serde_json::Error),
     #[error("Strip prefix error {0}")]
     StripPrefixError(#[from] std::path::StripPrefixError),
+    #[error("URL error {0}")]
+    Url(#[from] url::ParseError),
+    #[error("HTTP error {0}")]
+    Http(#[from] ureq::Error),
     #[error("Any error {0}")]
     Any(String),
 }
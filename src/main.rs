@@ -4,11 +4,13 @@
 
 mod builder;
 mod cmd;
+mod download;
 mod error;
 mod flathub;
 mod manifest;
 mod project;
 mod repo;
+mod xdg;
 
 use clap::{Parser, Subcommand};
 
@@ -26,6 +28,18 @@ enum Commands {
     Init(cmd::init::Args),
     /// Manage manifest.
     Manifest(cmd::manifest::Args),
+    /// Manage metainfo.
+    Metainfo(cmd::metainfo::Args),
+    /// Manage the desktop file.
+    Desktop(cmd::desktop::Args),
+    /// Mark the application end-of-life.
+    Eol(cmd::eol::Args),
+    /// Manage flathub.json.
+    FlathubJson(cmd::flathub_json::Args),
+    /// Check the icons.
+    Icon(cmd::icon::Args),
+    /// Manage releases.
+    Release(cmd::release::Args),
     /// Clone a flathub package.
     Clone(cmd::clone::Args),
     /*
@@ -37,9 +51,13 @@ enum Commands {
     */
     /// Cleanup build artifacts.
     Cleanup(cmd::cleanup::Args),
-    /*
     /// Manage modules.
-    Module,
+    Module(cmd::module::Args),
+    /// Manage the shared-modules submodule.
+    SharedModules(cmd::shared_modules::Args),
+    /// Manage the sources of the modules.
+    Source(cmd::source::Args),
+    /*
     /// Configure `flathub-cli`
     Configure,
     /// Update `flathub-cli` configuration
@@ -54,5 +72,14 @@ fn main() -> Result<()> {
         Commands::Cleanup(args) => cmd::cleanup::run(args),
         Commands::Clone(args) => cmd::clone::run(args),
         Commands::Manifest(args) => cmd::manifest::run(args),
+        Commands::Metainfo(args) => cmd::metainfo::run(args),
+        Commands::Desktop(args) => cmd::desktop::run(args),
+        Commands::Eol(args) => cmd::eol::run(args),
+        Commands::FlathubJson(args) => cmd::flathub_json::run(args),
+        Commands::Icon(args) => cmd::icon::run(args),
+        Commands::Release(args) => cmd::release::run(args),
+        Commands::Module(args) => cmd::module::run(args),
+        Commands::SharedModules(args) => cmd::shared_modules::run(args),
+        Commands::Source(args) => cmd::source::run(args),
     }
 }
@@ -0,0 +1,147 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Answers to the manifest creation prompts, from the command line
+//! or from an answer file.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::Result;
+
+/// Pre-filled answers for `Manifest::prompt_with_id`. Any `None`
+/// value will be prompted for, unless non interactive.
+///
+/// The answer file is either TOML or JSON, with the same kebab-case
+/// keys.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Answers {
+    /// The package type: `application`, `linux-audio-plugin` or `gimp-plugin`.
+    pub package_type: Option<String>,
+    /// The short id, if the project doesn't provide the id.
+    pub short_id: Option<String>,
+    /// The runtime. `NAME[//VERSION]`.
+    pub runtime: Option<String>,
+    /// The SDK. `NAME[//VERSION]`.
+    pub sdk: Option<String>,
+    /// The SDK extensions. `NAME[//VERSION]`.
+    pub sdk_extensions: Option<Vec<String>>,
+    /// The modules. Either a known module, a shared-modules path or
+    /// `NAME[:BUILDSYSTEM]` for a custom module.
+    pub modules: Option<Vec<String>>,
+    /// The build system for custom modules that don't specify one.
+    pub buildsystem: Option<String>,
+    /// The template to instantiate.
+    pub template: Option<String>,
+    /// The permission profiles of an application: `gui`, `audio`
+    /// or `network`.
+    pub finish_profiles: Option<Vec<String>>,
+    /// The command of an application.
+    pub command: Option<String>,
+    /// The icon file of an application, SVG or PNG.
+    pub icon: Option<String>,
+    /// The desktop categories of an application.
+    pub categories: Option<Vec<String>>,
+    /// The name of an application.
+    pub name: Option<String>,
+    /// The summary of an application.
+    pub summary: Option<String>,
+    /// The description of an application.
+    pub description: Option<String>,
+    /// The developer id, like a reverse DNS.
+    pub developer_id: Option<String>,
+    /// The developer name.
+    pub developer_name: Option<String>,
+    /// The SPDX project license.
+    pub license: Option<String>,
+    /// The URLs by AppStream type.
+    pub urls: Option<BTreeMap<String, String>>,
+    /// The OARS content attributes.
+    pub content_rating: Option<BTreeMap<String, String>>,
+    /// The brand colors, `light` and `dark`.
+    pub branding: Option<BTreeMap<String, String>>,
+    /// The screenshot image URLs.
+    pub screenshots: Option<Vec<String>>,
+    /// The version of the first release.
+    pub release: Option<String>,
+}
+
+impl Answers {
+    /// Load the answer file at `path`. JSON if the extension is `.json`,
+    /// TOML otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Answers> {
+        let content = std::fs::read_to_string(path.as_ref())?;
+        let answers = if path.as_ref().extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content)?
+        } else {
+            toml::from_str(&content)?
+        };
+
+        Ok(answers)
+    }
+
+    /// Merge `other` into `self`. Values from `other` take precedence.
+    pub fn merge(&mut self, other: Answers) {
+        fn merge_value<T>(value: &mut Option<T>, other: Option<T>) {
+            if other.is_some() {
+                *value = other;
+            }
+        }
+
+        merge_value(&mut self.package_type, other.package_type);
+        merge_value(&mut self.short_id, other.short_id);
+        merge_value(&mut self.runtime, other.runtime);
+        merge_value(&mut self.sdk, other.sdk);
+        merge_value(&mut self.sdk_extensions, other.sdk_extensions);
+        merge_value(&mut self.modules, other.modules);
+        merge_value(&mut self.buildsystem, other.buildsystem);
+        merge_value(&mut self.template, other.template);
+        merge_value(&mut self.finish_profiles, other.finish_profiles);
+        merge_value(&mut self.command, other.command);
+        merge_value(&mut self.icon, other.icon);
+        merge_value(&mut self.categories, other.categories);
+        merge_value(&mut self.name, other.name);
+        merge_value(&mut self.summary, other.summary);
+        merge_value(&mut self.description, other.description);
+        merge_value(&mut self.developer_id, other.developer_id);
+        merge_value(&mut self.developer_name, other.developer_name);
+        merge_value(&mut self.license, other.license);
+        merge_value(&mut self.urls, other.urls);
+        merge_value(&mut self.content_rating, other.content_rating);
+        merge_value(&mut self.branding, other.branding);
+        merge_value(&mut self.screenshots, other.screenshots);
+        merge_value(&mut self.release, other.release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_merge() {
+        let mut answers: Answers = toml::from_str(
+            r#"
+package-type = "application"
+runtime = "gnome"
+sdk-extensions = []
+modules = ["fltk", "foo:meson"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(answers.sdk_extensions, Some(vec![]));
+
+        answers.merge(Answers {
+            runtime: Some("kde".to_string()),
+            ..Answers::default()
+        });
+        assert_eq!(answers.package_type.as_deref(), Some("application"));
+        assert_eq!(answers.runtime.as_deref(), Some("kde"));
+        assert_eq!(answers.modules.as_ref().map(Vec::len), Some(2));
+        assert!(answers.sdk.is_none());
+    }
+}
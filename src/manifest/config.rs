@@ -4,7 +4,7 @@
 
 use std::collections::HashMap;
 
-use super::module::{Module, ModuleEntry, Source, SourceType};
+use super::module::{Module, ModuleEntry, Source};
 
 pub(crate) const FREEDESKTOP_VERSION: &str = "25.08";
 pub(crate) const GNOME_VERSION: &str = "49";
@@ -22,13 +22,10 @@ fn fltk_module() -> Module {
             "--disable-shared".to_string(),
             "--enable-static".to_string(),
         ]),
-        sources: vec![Source {
-            type_: SourceType::Archive,
-            path: "https://github.com/fltk/fltk/archive/release-1.3.5.tar.gz".to_string(),
-            sha256: Some(
-                "5c534287b0e03b9520ff866704a5649268986b371bdf8f6ac003fa240e761901".to_string(),
-            ),
-        }],
+        sources: vec![Source::archive(
+            "https://github.com/fltk/fltk/archive/release-1.3.5.tar.gz",
+            "5c534287b0e03b9520ff866704a5649268986b371bdf8f6ac003fa240e761901",
+        )],
         cleanup: Some(vec![
             "/bin".to_string(),
             "/include".to_string(),
@@ -67,7 +64,7 @@ lazy_static::lazy_static! {
                 "fftw3f-static",
                 ModuleEntry::SharedModule("shared-modules/linux-audio/fftw3f-static.json".into()),
             ),
-            ("fltk", ModuleEntry::Module(fltk_module())),
+            ("fltk", ModuleEntry::Module(Box::new(fltk_module()))),
         ])
     };
 }
@@ -0,0 +1,368 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Offline checks of the desktop file.
+
+use std::collections::HashSet;
+
+use serde_json::Value as JsonValue;
+
+use super::MAIN_CATEGORIES;
+use crate::manifest::diagnostic::{Diagnostic, Severity};
+use crate::manifest::file as manifest_file;
+
+const DESKTOP_ENTRY: &str = "Desktop Entry";
+
+/// Keys whose value is a boolean.
+const BOOLEAN_KEYS: &[&str] = &[
+    "NoDisplay",
+    "Hidden",
+    "DBusActivatable",
+    "Terminal",
+    "StartupNotify",
+    "PrefersNonDefaultGPU",
+    "SingleMainWindow",
+];
+
+/// A key of the desktop file: line, key and value.
+type Key<'a> = (u32, &'a str, &'a str);
+
+/// What to check the desktop file against, from the manifest.
+pub(crate) struct CheckContext<'a> {
+    /// The id of the manifest.
+    pub id: &'a str,
+    /// The `command` of the manifest.
+    pub command: Option<&'a str>,
+    /// The `rename-desktop-file` of the manifest.
+    pub rename_desktop_file: Option<&'a str>,
+    /// The `rename-icon` of the manifest.
+    pub rename_icon: Option<&'a str>,
+}
+
+impl<'a> CheckContext<'a> {
+    /// The context from the JSON `manifest`. `None` if there is no id.
+    pub fn from_manifest(manifest: &'a JsonValue) -> Option<Self> {
+        let string = |key| manifest.get(key).and_then(JsonValue::as_str);
+
+        Some(CheckContext {
+            id: manifest_file::id(manifest)?,
+            command: string("command"),
+            rename_desktop_file: string("rename-desktop-file"),
+            rename_icon: string("rename-icon"),
+        })
+    }
+}
+
+/// Whether `key` is a valid key, with an optional `[locale]`.
+fn is_valid_key(key: &str) -> bool {
+    let name = match key.split_once('[') {
+        Some((name, locale)) => {
+            if !locale.ends_with(']') || locale.len() < 2 {
+                return false;
+            }
+            name
+        }
+        None => key,
+    };
+
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+struct Checker<'a> {
+    context: &'a CheckContext<'a>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn report<S: Into<String>>(&mut self, line: u32, severity: Severity, message: S) {
+        self.diagnostics.push(Diagnostic {
+            line,
+            column: 1,
+            severity,
+            message: message.into(),
+        });
+    }
+
+    fn check_file_name(&mut self, file_name: &str) {
+        let expected = format!("{}.desktop", self.context.id);
+        match self.context.rename_desktop_file {
+            Some(rename) if rename != file_name => self.report(
+                1,
+                Severity::Error,
+                format!("file is '{file_name}' but the manifest renames '{rename}'"),
+            ),
+            Some(_) => {}
+            None if file_name != expected => self.report(
+                1,
+                Severity::Error,
+                format!("file should be '{expected}', or set rename-desktop-file to '{file_name}' in the manifest"),
+            ),
+            None => {}
+        }
+    }
+
+    fn check_icon(&mut self, line: u32, icon: &str) {
+        if icon.contains('/') {
+            self.report(
+                line,
+                Severity::Error,
+                format!("Icon '{icon}' must be a name, not a path"),
+            );
+            return;
+        }
+        if [".png", ".svg", ".xpm"]
+            .iter()
+            .any(|ext| icon.ends_with(ext))
+        {
+            self.report(
+                line,
+                Severity::Error,
+                format!("Icon '{icon}' must not have an extension"),
+            );
+        }
+        let id = self.context.id;
+        match self.context.rename_icon {
+            Some(rename) if rename != icon => self.report(
+                line,
+                Severity::Error,
+                format!("Icon '{icon}' doesn't match rename-icon '{rename}' in the manifest"),
+            ),
+            Some(_) => {}
+            None if icon != id && !icon.starts_with(&format!("{id}.")) => self.report(
+                line,
+                Severity::Error,
+                format!("Icon '{icon}' should be '{id}', or set rename-icon to '{icon}' in the manifest"),
+            ),
+            None => {}
+        }
+    }
+
+    fn check_exec(&mut self, line: u32, exec: &str) {
+        let Some(command) = self.context.command else {
+            return;
+        };
+        let program = exec.split_whitespace().next().unwrap_or_default();
+        let base_name = |s: &str| s.rsplit('/').next().unwrap_or_default().to_string();
+        if program != command && base_name(program) != base_name(command) {
+            self.report(
+                line,
+                Severity::Warning,
+                format!("Exec '{program}' doesn't match the manifest command '{command}'"),
+            );
+        }
+    }
+
+    fn check_categories(&mut self, line: u32, categories: &str) {
+        if !categories.ends_with(';') {
+            self.report(line, Severity::Warning, "Categories must end with ';'");
+        }
+        if !categories
+            .split(';')
+            .any(|category| MAIN_CATEGORIES.contains(&category))
+        {
+            self.report(
+                line,
+                Severity::Warning,
+                format!(
+                    "Categories has no main category: {}",
+                    MAIN_CATEGORIES.join(", ")
+                ),
+            );
+        }
+    }
+
+    /// Check the keys of the desktop entry group.
+    fn check_entry(&mut self, group_line: u32, keys: &[Key]) {
+        let find = |name: &str| keys.iter().find(|(_, key, _)| *key == name);
+
+        match find("Type") {
+            None => self.report(group_line, Severity::Error, "missing key Type"),
+            Some((line, _, value)) if *value != "Application" => self.report(
+                *line,
+                Severity::Error,
+                format!("Type is '{value}', expected 'Application'"),
+            ),
+            Some(_) => {}
+        }
+        if find("Name").is_none() {
+            self.report(group_line, Severity::Error, "missing key Name");
+        }
+        match find("Exec") {
+            None if find("DBusActivatable").is_none_or(|(_, _, value)| *value != "true") => {
+                self.report(group_line, Severity::Error, "missing key Exec")
+            }
+            None => {}
+            Some((line, _, exec)) => self.check_exec(*line, exec),
+        }
+        match find("Icon") {
+            None => self.report(group_line, Severity::Error, "missing key Icon"),
+            Some((line, _, icon)) => self.check_icon(*line, icon),
+        }
+        match find("Categories") {
+            None => self.report(group_line, Severity::Warning, "missing key Categories"),
+            Some((line, _, categories)) => self.check_categories(*line, categories),
+        }
+        for (line, key, value) in keys {
+            if BOOLEAN_KEYS.contains(key) && !matches!(*value, "true" | "false") {
+                self.report(
+                    *line,
+                    Severity::Error,
+                    format!("{key} must be 'true' or 'false', not '{value}'"),
+                );
+            }
+        }
+    }
+}
+
+/// Check the desktop file `content` named `file_name`. Return the
+/// diagnostics, sorted by line.
+pub(crate) fn check(content: &str, file_name: &str, context: &CheckContext) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        context,
+        diagnostics: vec![],
+    };
+    checker.check_file_name(file_name);
+
+    let mut groups = HashSet::new();
+    let mut group: Option<(u32, &str)> = None;
+    let mut entry: Option<(u32, Vec<Key>)> = None;
+    let mut keys = HashSet::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line_number = idx as u32 + 1;
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if group.is_none() && name != DESKTOP_ENTRY {
+                checker.report(
+                    line_number,
+                    Severity::Error,
+                    format!("first group must be [{DESKTOP_ENTRY}]"),
+                );
+            }
+            if !groups.insert(name) {
+                checker.report(
+                    line_number,
+                    Severity::Error,
+                    format!("duplicate group [{name}]"),
+                );
+            }
+            if name == DESKTOP_ENTRY && entry.is_none() {
+                entry = Some((line_number, vec![]));
+            }
+            group = Some((line_number, name));
+            keys.clear();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            checker.report(line_number, Severity::Error, "invalid line");
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let Some((group_line, group_name)) = group else {
+            checker.report(line_number, Severity::Error, "key outside of a group");
+            continue;
+        };
+        if !is_valid_key(key) {
+            checker.report(line_number, Severity::Error, format!("invalid key '{key}'"));
+        }
+        if !keys.insert(key) {
+            checker.report(line_number, Severity::Error, format!("duplicate key {key}"));
+        }
+        if let Some((entry_line, ref mut entry_keys)) = entry {
+            if group_name == DESKTOP_ENTRY && group_line == entry_line {
+                entry_keys.push((line_number, key, value));
+            }
+        }
+    }
+
+    match entry {
+        Some((line, keys)) => checker.check_entry(line, &keys),
+        None => checker.report(1, Severity::Error, format!("missing [{DESKTOP_ENTRY}]")),
+    }
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTEXT: CheckContext = CheckContext {
+        id: "org.example.App",
+        command: Some("app"),
+        rename_desktop_file: None,
+        rename_icon: None,
+    };
+
+    fn messages(content: &str, file_name: &str, context: &CheckContext) -> Vec<String> {
+        check(content, file_name, context)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_check_desktop() {
+        let desktop = include_str!("../testdata/application.desktop");
+        assert!(messages(desktop, "org.example.App.desktop", &CONTEXT).is_empty());
+
+        let desktop = "# Comment\n\
+                       [Desktop Entry]\n\
+                       Type=Application\n\
+                       Name=App\n\
+                       Name[fr]=Appli\n\
+                       Exec=/usr/bin/other %U\n\
+                       Icon=app.png\n\
+                       Terminal=no\n\
+                       Terminal=false\n\
+                       Categories=Foo\n\
+                       [Desktop Action new]\n\
+                       Name=New\n";
+        assert_eq!(
+            messages(desktop, "app.desktop", &CONTEXT),
+            [
+                "1:1: error: file should be 'org.example.App.desktop', or set rename-desktop-file to 'app.desktop' in the manifest",
+                "6:1: warning: Exec '/usr/bin/other' doesn't match the manifest command 'app'",
+                "7:1: error: Icon 'app.png' must not have an extension",
+                "7:1: error: Icon 'app.png' should be 'org.example.App', or set rename-icon to 'app.png' in the manifest",
+                "8:1: error: Terminal must be 'true' or 'false', not 'no'",
+                "9:1: error: duplicate key Terminal",
+                "10:1: warning: Categories must end with ';'",
+                "10:1: warning: Categories has no main category: AudioVideo, Audio, Video, Development, Education, Game, Graphics, Network, Office, Science, Settings, System, Utility",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_desktop_renames() {
+        let context = CheckContext {
+            rename_desktop_file: Some("app.desktop"),
+            rename_icon: Some("app"),
+            ..CONTEXT
+        };
+        let desktop = "[Desktop Entry]\n\
+                       Type=Application\n\
+                       Name=App\n\
+                       Exec=app\n\
+                       Icon=app\n\
+                       Categories=Utility;\n";
+        assert!(messages(desktop, "app.desktop", &context).is_empty());
+        assert_eq!(
+            messages(desktop, "org.example.App.desktop", &context),
+            ["1:1: error: file is 'org.example.App.desktop' but the manifest renames 'app.desktop'"]
+        );
+        assert_eq!(
+            messages("Name=App\n", "app.desktop", &context),
+            [
+                "1:1: error: key outside of a group",
+                "1:1: error: missing [Desktop Entry]"
+            ]
+        );
+    }
+}
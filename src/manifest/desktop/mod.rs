@@ -0,0 +1,85 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Desktop entry for applications.
+
+pub(crate) mod check;
+
+use std::path::{Path, PathBuf};
+
+use crate::{manifest::Manifest, Result};
+
+/// The main categories from the desktop menu specification.
+pub(crate) const MAIN_CATEGORIES: &[&str] = &[
+    "AudioVideo",
+    "Audio",
+    "Video",
+    "Development",
+    "Education",
+    "Game",
+    "Graphics",
+    "Network",
+    "Office",
+    "Science",
+    "Settings",
+    "System",
+    "Utility",
+];
+
+/// The category when none is given.
+pub(crate) const DEFAULT_CATEGORY: &str = "Utility";
+
+/// A desktop entry to generate.
+pub(crate) struct DesktopEntry<'a> {
+    /// The application id. The icon has the same name.
+    pub id: &'a str,
+    pub name: &'a str,
+    /// The command, as in the manifest.
+    pub command: &'a str,
+    pub categories: &'a [String],
+}
+
+impl DesktopEntry<'_> {
+    /// The content of the desktop file.
+    pub fn content(&self) -> String {
+        let mut categories = self.categories.join(";");
+        if categories.is_empty() {
+            categories.push_str(DEFAULT_CATEGORY);
+        }
+
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name={}\n\
+             Exec={}\n\
+             Icon={}\n\
+             Terminal=false\n\
+             Categories={categories};\n",
+            self.name, self.command, self.id,
+        )
+    }
+
+    /// Write `<id>.desktop` in `dest_dir` and return its path.
+    pub fn write(&self, dest_dir: &Path) -> Result<PathBuf> {
+        let desktop_file = dest_dir.join(format!("{}.desktop", self.id));
+        std::fs::write(&desktop_file, self.content())?;
+
+        Ok(desktop_file)
+    }
+}
+
+/// Generate the desktop file for the application and return its path.
+pub fn generate(manifest: &Manifest, dest_dir: &Path) -> Result<PathBuf> {
+    DesktopEntry {
+        id: &manifest.id,
+        name: manifest
+            .metainfo
+            .name
+            .as_deref()
+            .unwrap_or(&manifest.short_id),
+        command: manifest.command.as_deref().unwrap_or_default(),
+        categories: &manifest.categories,
+    }
+    .write(dest_dir)
+}
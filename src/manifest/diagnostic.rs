@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Diagnostics of the offline checks.
+
+use std::path::Path;
+
+use crate::{anyerror, Result};
+
+/// Severity of a diagnostic.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Error => "error",
+                Self::Warning => "warning",
+            }
+        )
+    }
+}
+
+/// A problem found in a checked file, the metainfo or the desktop
+/// file, at a line and column.
+#[derive(Debug)]
+pub(crate) struct Diagnostic {
+    pub line: u32,
+    pub column: u32,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
+
+/// Print the `diagnostics` found in `file`. Fails if there is any
+/// error.
+pub(crate) fn report(file: &Path, diagnostics: &[Diagnostic]) -> Result<()> {
+    for diagnostic in diagnostics {
+        println!("{}:{diagnostic}", file.display());
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(anyerror!(format!("{errors} error(s) in {file:?}")));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,296 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Existing manifest files.
+
+use std::path::{Component, Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::{anyerror, error::Context, Result};
+
+/// The indent of new manifests.
+const DEFAULT_INDENT: &str = "    ";
+
+/// Whether the manifest file at `path` is JSON, based on the extension.
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Load the JSON manifest file at `path`.
+pub fn load(path: &Path) -> Result<JsonValue> {
+    if !is_json(path) {
+        return Err(anyerror!(format!(
+            "{path:?}: only JSON manifests are supported"
+        )));
+    }
+    let content = std::fs::read_to_string(path).with_context(|| format!("Reading {path:?}"))?;
+
+    serde_json::from_str(&content).with_context(|| format!("Parsing {path:?}"))
+}
+
+/// Return the id of the manifest.
+pub fn id(manifest: &JsonValue) -> Option<&str> {
+    manifest
+        .get("id")
+        .or_else(|| manifest.get("app-id"))
+        .and_then(JsonValue::as_str)
+}
+
+/// Return the name of the main module, the last one.
+pub fn main_module(manifest: &JsonValue) -> Option<&str> {
+    modules(manifest)
+        .iter()
+        .rev()
+        .find_map(|module| module.get("name").and_then(JsonValue::as_str))
+}
+
+/// Detect the indent of the JSON `content`, from the first indented
+/// line.
+fn detect_indent(content: &str) -> String {
+    content
+        .lines()
+        .find_map(|line| {
+            let trimmed = line.trim_start();
+            (!trimmed.is_empty() && trimmed.len() != line.len())
+                .then(|| line[..line.len() - trimmed.len()].to_string())
+        })
+        .unwrap_or_else(|| DEFAULT_INDENT.to_string())
+}
+
+/// Save the JSON `manifest` to `path`, keeping the indent and the
+/// final newline of the existing file.
+pub fn save(path: &Path, manifest: &JsonValue) -> Result<()> {
+    let existing = std::fs::read_to_string(path).ok();
+    let indent = existing
+        .as_deref()
+        .map(detect_indent)
+        .unwrap_or_else(|| DEFAULT_INDENT.to_string());
+    let final_newline = existing.as_deref().is_none_or(|c| c.ends_with('\n'));
+
+    let mut content = vec![];
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+    manifest.serialize(&mut serializer)?;
+    if final_newline {
+        content.push(b'\n');
+    }
+
+    std::fs::write(path, content).with_context(|| format!("Writing {path:?}"))
+}
+
+/// Return the modules of the manifest or of a module.
+pub fn modules(value: &JsonValue) -> &[JsonValue] {
+    value
+        .get("modules")
+        .and_then(JsonValue::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Return the modules of the manifest or of a module, adding them if
+/// needed. `None` if `value` isn't an object.
+pub fn modules_mut(value: &mut JsonValue) -> Option<&mut Vec<JsonValue>> {
+    value
+        .as_object_mut()?
+        .entry("modules")
+        .or_insert_with(|| JsonValue::Array(vec![]))
+        .as_array_mut()
+}
+
+/// The name of a module entry: the name of the module, or the path of
+/// an included file.
+pub fn module_name(entry: &JsonValue) -> Option<&str> {
+    match entry {
+        JsonValue::String(path) => Some(path),
+        _ => entry.get("name").and_then(JsonValue::as_str),
+    }
+}
+
+/// Find the module `name` in the module tree of `manifest`. Return
+/// the indices of the module at each level.
+pub fn find_module(manifest: &JsonValue, name: &str) -> Option<Vec<usize>> {
+    modules(manifest)
+        .iter()
+        .enumerate()
+        .find_map(|(idx, entry)| {
+            if module_name(entry) == Some(name) {
+                return Some(vec![idx]);
+            }
+            find_module(entry, name).map(|mut indices| {
+                indices.insert(0, idx);
+                indices
+            })
+        })
+}
+
+/// Return the module at `indices`, as returned by `find_module`.
+pub fn module_at_mut<'a>(
+    manifest: &'a mut JsonValue,
+    indices: &[usize],
+) -> Option<&'a mut JsonValue> {
+    indices.iter().try_fold(manifest, |value, idx| {
+        value.get_mut("modules")?.as_array_mut()?.get_mut(*idx)
+    })
+}
+
+/// Return the modules containing the module at `indices`, as
+/// returned by `find_module`.
+pub fn parent_modules_mut<'a>(
+    manifest: &'a mut JsonValue,
+    indices: &[usize],
+) -> Option<&'a mut Vec<JsonValue>> {
+    let (_, parent) = indices.split_last()?;
+    match parent {
+        [] => modules_mut(manifest),
+        _ => module_at_mut(manifest, parent).and_then(modules_mut),
+    }
+}
+
+/// Remove the module at `indices` and return it. The modules of the
+/// parent module are removed if empty.
+pub fn remove_module(manifest: &mut JsonValue, indices: &[usize]) -> Option<JsonValue> {
+    let (idx, parent) = indices.split_last()?;
+    let modules = parent_modules_mut(manifest, indices)?;
+    if *idx >= modules.len() {
+        return None;
+    }
+    let module = modules.remove(*idx);
+    if modules.is_empty() && !parent.is_empty() {
+        if let Some(parent) = module_at_mut(manifest, parent).and_then(JsonValue::as_object_mut) {
+            parent.shift_remove("modules");
+        }
+    }
+
+    Some(module)
+}
+
+/// Rebase the relative `path`, from the directory `from_dir` to the
+/// directory `to_dir`. Both directories are relative to the same one.
+pub fn rebase_path(path: &str, from_dir: &Path, to_dir: &Path) -> String {
+    if Path::new(path).is_absolute() {
+        return path.to_string();
+    }
+    let mut rebased = PathBuf::new();
+    let path = from_dir.join(path);
+    let components = to_dir
+        .components()
+        .map(|_| Component::ParentDir)
+        .chain(path.components());
+    for component in components {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(rebased.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                rebased.pop();
+            }
+            _ => rebased.push(component),
+        }
+    }
+
+    rebased.to_string_lossy().into_owned()
+}
+
+/// Rebase the relative paths of the `module` moved from the file in
+/// `from_dir` to a file in `to_dir`: the paths of the sources, the
+/// included sources and modules, recursively.
+pub fn rebase_module(module: &mut JsonValue, from_dir: &Path, to_dir: &Path) {
+    let rebase = |value: &mut JsonValue| {
+        if let JsonValue::String(path) = value {
+            *path = rebase_path(path, from_dir, to_dir);
+        }
+    };
+    if let JsonValue::String(_) = module {
+        return rebase(module);
+    }
+    if let Some(sources) = module.get_mut("sources").and_then(JsonValue::as_array_mut) {
+        for source in sources {
+            match source {
+                JsonValue::String(_) => rebase(source),
+                _ => {
+                    source.get_mut("path").map(rebase);
+                    if let Some(paths) = source.get_mut("paths").and_then(JsonValue::as_array_mut) {
+                        paths.iter_mut().for_each(rebase);
+                    }
+                }
+            }
+        }
+    }
+    if let Some(modules) = module.get_mut("modules").and_then(JsonValue::as_array_mut) {
+        for module in modules {
+            rebase_module(module, from_dir, to_dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_modules() {
+        let mut manifest = json!({
+            "id": "org.example.App",
+            "modules": [
+                "shared-modules/lua5.4/lua-5.4.json",
+                { "name": "app", "modules": [ { "name": "dep" } ] }
+            ]
+        });
+        assert_eq!(find_module(&manifest, "dep"), Some(vec![1, 0]));
+        assert_eq!(
+            find_module(&manifest, "shared-modules/lua5.4/lua-5.4.json"),
+            Some(vec![0])
+        );
+        assert_eq!(find_module(&manifest, "foo"), None);
+
+        let dep = module_at_mut(&mut manifest, &[1, 0]).unwrap();
+        modules_mut(dep).unwrap().push(json!({ "name": "foo" }));
+        assert_eq!(find_module(&manifest, "foo"), Some(vec![1, 0, 0]));
+        assert_eq!(
+            remove_module(&mut manifest, &[1, 0]),
+            Some(json!({ "name": "dep", "modules": [ { "name": "foo" } ] }))
+        );
+        assert_eq!(manifest["modules"][1], json!({ "name": "app" }));
+        assert_eq!(find_module(&manifest, "foo"), None);
+
+        let mut module = json!({
+            "name": "dep",
+            "sources": [
+                { "type": "patch", "paths": [ "fix.patch" ] },
+                { "type": "file", "path": "/usr/share/dep.conf" },
+                "dep-sources.json"
+            ],
+            "modules": [ "shared-modules/lua5.4/lua-5.4.json" ]
+        });
+        let original = module.clone();
+        rebase_module(&mut module, Path::new(""), Path::new("modules"));
+        assert_eq!(
+            module,
+            json!({
+                "name": "dep",
+                "sources": [
+                    { "type": "patch", "paths": [ "../fix.patch" ] },
+                    { "type": "file", "path": "/usr/share/dep.conf" },
+                    "../dep-sources.json"
+                ],
+                "modules": [ "../shared-modules/lua5.4/lua-5.4.json" ]
+            })
+        );
+        rebase_module(&mut module, Path::new("modules"), Path::new(""));
+        assert_eq!(module, original);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest.json");
+        std::fs::write(&path, "{\n  \"id\": \"org.example.App\"\n}").unwrap();
+        save(&path, &json!({ "id": "org.example.App", "modules": [] })).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\n  \"id\": \"org.example.App\",\n  \"modules\": []\n}"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The `finish-args`, ie the sandbox permissions.
+
+use super::macros::string_enum;
+
+/// A `--socket=`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Socket {
+    X11,
+    FallbackX11,
+    Wayland,
+    InheritWayland,
+    Pulseaudio,
+    SessionBus,
+    SystemBus,
+    SshAuth,
+    Pcsc,
+    Cups,
+    GpgAgent,
+}
+
+/// A `--device=`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Device {
+    Dri,
+    Input,
+    Usb,
+    Kvm,
+    Shm,
+    All,
+}
+
+/// A `--share=`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Share {
+    Network,
+    Ipc,
+}
+
+string_enum!(Socket,
+    X11 => "x11",
+    FallbackX11 => "fallback-x11",
+    Wayland => "wayland",
+    InheritWayland => "inherit-wayland-socket",
+    Pulseaudio => "pulseaudio",
+    SessionBus => "session-bus",
+    SystemBus => "system-bus",
+    SshAuth => "ssh-auth",
+    Pcsc => "pcsc",
+    Cups => "cups",
+    GpgAgent => "gpg-agent",
+);
+
+string_enum!(Device,
+    Dri => "dri",
+    Input => "input",
+    Usb => "usb",
+    Kvm => "kvm",
+    Shm => "shm",
+    All => "all",
+);
+
+string_enum!(Share,
+    Network => "network",
+    Ipc => "ipc",
+);
+
+/// Common application profiles.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Profile {
+    /// Graphical application: Wayland with X11 fallback, and GPU.
+    Gui,
+    /// Audio playback and recording.
+    Audio,
+    /// Network access.
+    Network,
+}
+
+string_enum!(Profile,
+    Gui => "gui",
+    Audio => "audio",
+    Network => "network",
+);
+
+/// The `finish-args`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct FinishArgs {
+    pub sockets: Vec<Socket>,
+    pub devices: Vec<Device>,
+    pub shares: Vec<Share>,
+    pub filesystems: Vec<String>,
+    pub talk_names: Vec<String>,
+    pub own_names: Vec<String>,
+    pub env: Vec<(String, String)>,
+    /// Any other argument, kept verbatim.
+    pub other: Vec<String>,
+}
+
+fn push_unique<T: PartialEq>(values: &mut Vec<T>, value: T) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+impl FinishArgs {
+    pub fn is_empty(&self) -> bool {
+        self == &FinishArgs::default()
+    }
+
+    pub fn add_socket(&mut self, socket: Socket) {
+        push_unique(&mut self.sockets, socket);
+    }
+
+    pub fn add_device(&mut self, device: Device) {
+        push_unique(&mut self.devices, device);
+    }
+
+    pub fn add_share(&mut self, share: Share) {
+        push_unique(&mut self.shares, share);
+    }
+
+    /// Add the permissions for `profile`.
+    pub fn add_profile(&mut self, profile: &Profile) {
+        match profile {
+            Profile::Gui => {
+                self.add_share(Share::Ipc);
+                self.add_socket(Socket::FallbackX11);
+                self.add_socket(Socket::Wayland);
+                self.add_device(Device::Dri);
+            }
+            Profile::Audio => self.add_socket(Socket::Pulseaudio),
+            Profile::Network => self.add_share(Share::Network),
+        }
+    }
+
+    /// Add the argument `arg`, like `--socket=wayland`. Anything not
+    /// modeled is kept as is.
+    pub fn add_arg(&mut self, arg: &str) {
+        let parsed = arg
+            .strip_prefix("--")
+            .and_then(|arg| arg.split_once('='))
+            .and_then(|(key, value)| {
+                match key {
+                    "socket" => self.add_socket(value.parse().ok()?),
+                    "device" => self.add_device(value.parse().ok()?),
+                    "share" => self.add_share(value.parse().ok()?),
+                    "filesystem" => push_unique(&mut self.filesystems, value.to_string()),
+                    "talk-name" => push_unique(&mut self.talk_names, value.to_string()),
+                    "own-name" => push_unique(&mut self.own_names, value.to_string()),
+                    "env" => {
+                        let (name, value) = value.split_once('=')?;
+                        self.env.retain(|(n, _)| n != name);
+                        self.env.push((name.to_string(), value.to_string()));
+                    }
+                    _ => return None,
+                }
+                Some(())
+            });
+        if parsed.is_none() {
+            push_unique(&mut self.other, arg.to_string());
+        }
+    }
+
+    /// Make the combination of sockets consistent: X11 as a fallback
+    /// when there is Wayland, and `--share=ipc` for X11.
+    pub fn normalize(&mut self) {
+        if self.sockets.contains(&Socket::Wayland) && self.sockets.contains(&Socket::X11) {
+            self.sockets.retain(|s| *s != Socket::X11);
+            self.add_socket(Socket::FallbackX11);
+        }
+        if self.sockets.contains(&Socket::FallbackX11) {
+            self.sockets.retain(|s| *s != Socket::X11);
+            self.add_socket(Socket::Wayland);
+        }
+        if self.sockets.contains(&Socket::X11) || self.sockets.contains(&Socket::FallbackX11) {
+            self.add_share(Share::Ipc);
+        }
+    }
+
+    /// Return the arguments, normalized.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut finish_args = self.clone();
+        finish_args.normalize();
+
+        let mut args = vec![];
+        args.extend(finish_args.shares.iter().map(|s| format!("--share={s}")));
+        args.extend(finish_args.sockets.iter().map(|s| format!("--socket={s}")));
+        args.extend(finish_args.devices.iter().map(|d| format!("--device={d}")));
+        args.extend(
+            finish_args
+                .filesystems
+                .iter()
+                .map(|f| format!("--filesystem={f}")),
+        );
+        args.extend(
+            finish_args
+                .talk_names
+                .iter()
+                .map(|n| format!("--talk-name={n}")),
+        );
+        args.extend(
+            finish_args
+                .own_names
+                .iter()
+                .map(|n| format!("--own-name={n}")),
+        );
+        args.extend(
+            finish_args
+                .env
+                .iter()
+                .map(|(n, v)| format!("--env={n}={v}")),
+        );
+        args.extend(finish_args.other);
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        let mut finish_args = FinishArgs::default();
+        finish_args.add_profile(&Profile::Gui);
+        finish_args.add_profile(&Profile::Audio);
+        finish_args.add_profile(&Profile::Network);
+        assert_eq!(
+            finish_args.to_args(),
+            [
+                "--share=ipc",
+                "--share=network",
+                "--socket=fallback-x11",
+                "--socket=wayland",
+                "--socket=pulseaudio",
+                "--device=dri",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut finish_args = FinishArgs::default();
+        for arg in [
+            "--socket=x11",
+            "--socket=wayland",
+            "--env=FOO=bar",
+            "--filesystem=xdg-music:ro",
+            "--persist=.foo",
+            "--socket=bogus",
+        ] {
+            finish_args.add_arg(arg);
+        }
+        assert_eq!(
+            finish_args.to_args(),
+            [
+                "--share=ipc",
+                "--socket=wayland",
+                "--socket=fallback-x11",
+                "--filesystem=xdg-music:ro",
+                "--env=FOO=bar",
+                "--persist=.foo",
+                "--socket=bogus",
+            ]
+        );
+
+        let mut finish_args = FinishArgs::default();
+        finish_args.add_arg("--socket=x11");
+        assert_eq!(finish_args.to_args(), ["--share=ipc", "--socket=x11"]);
+    }
+}
@@ -1,30 +1,246 @@
-// SPDX-FileCopyrightText: 2020-2023 Hubert Figuière
+// SPDX-FileCopyrightText: 2020-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use serde_json::json;
+//! The `flathub.json` file.
 
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::macros::string_enum;
+use crate::{anyerror, error::Context, Result};
+
+const FLATHUB_JSON: &str = "flathub.json";
+
+/// The architectures built by Flathub.
+pub(crate) const ARCHES: &[&str] = &["x86_64", "aarch64"];
+
+/// The keys of `flathub.json`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Key {
+    OnlyArches,
+    SkipArches,
+    SkipIconsCheck,
+    EndOfLife,
+    EndOfLifeRebase,
+    AutomergeFlathubbotPrs,
+    DisableExternalDataChecker,
+    PublishDelayHours,
+}
+
+string_enum!(Key,
+    OnlyArches => "only-arches",
+    SkipArches => "skip-arches",
+    SkipIconsCheck => "skip-icons-check",
+    EndOfLife => "end-of-life",
+    EndOfLifeRebase => "end-of-life-rebase",
+    AutomergeFlathubbotPrs => "automerge-flathubbot-prs",
+    DisableExternalDataChecker => "disable-external-data-checker",
+    PublishDelayHours => "publish-delay-hours",
+);
+
+/// The content of `flathub.json`. Unknown keys are preserved.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Flathub {
-    pub skip_icons_check: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only_arches: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_arches: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_icons_check: Option<bool>,
+    /// The end of life message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_of_life: Option<String>,
+    /// The id of the app replacing this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_of_life_rebase: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub automerge_flathubbot_prs: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_external_data_checker: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_delay_hours: Option<u32>,
+    /// The unknown keys.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, JsonValue>,
+}
+
+/// Parse `true` or `false`.
+fn parse_bool(value: &str) -> Result<bool> {
+    value
+        .parse()
+        .map_err(|_| anyerror!(format!("'{value}' isn't true or false")))
+}
+
+/// Parse a comma separated list of architectures.
+fn parse_arches(value: &str) -> Result<Vec<String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|arch| {
+            if ARCHES.contains(&arch) {
+                Ok(arch.to_string())
+            } else {
+                Err(anyerror!(format!(
+                    "Unknown architecture '{arch}', expected one of {}",
+                    ARCHES.join(", ")
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Whether `id` looks like an application id.
+pub(crate) fn is_valid_app_id(id: &str) -> bool {
+    let parts: Vec<_> = id.split('.').collect();
+    parts.len() >= 3
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && !part.starts_with(|c: char| c.is_ascii_digit())
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
 }
 
 impl Flathub {
-    /// Generate the flathub.json file and return its path.
-    pub fn generate(
-        &self,
-        dest_dir: &std::path::Path,
-    ) -> Result<std::path::PathBuf, std::io::Error> {
-        let mut flathub_file = dest_dir.to_path_buf();
-        flathub_file.push("flathub.json");
-
-        let mut j = json!({});
-        if self.skip_icons_check {
-            j["skip-icons-check"] = json!(self.skip_icons_check);
+    /// Load `flathub.json` from `dir`. Empty if it doesn't exist.
+    pub fn load(dir: &Path) -> Result<Flathub> {
+        let path = dir.join(FLATHUB_JSON);
+        if !path.exists() {
+            return Ok(Flathub::default());
         }
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("Reading {path:?}"))?;
+
+        serde_json::from_str(&content).with_context(|| format!("Parsing {path:?}"))
+    }
+
+    /// Generate the flathub.json file in `dest_dir` and return its path.
+    pub fn generate(&self, dest_dir: &Path) -> Result<PathBuf> {
+        let flathub_file = dest_dir.join(FLATHUB_JSON);
 
         let mut file = std::fs::File::create(&flathub_file)?;
-        serde_json::to_writer_pretty(&mut file, &j)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
 
         Ok(flathub_file)
     }
+
+    /// Get the value of `key`.
+    pub fn get(&self, key: Key) -> Option<JsonValue> {
+        serde_json::to_value(self)
+            .ok()?
+            .get(key.to_string())
+            .cloned()
+    }
+
+    /// Set `key` to `value`, parsed and validated. Nothing is changed
+    /// on error.
+    pub fn set(&mut self, key: Key, value: &str) -> Result<()> {
+        let mut flathub = self.clone();
+        flathub.set_value(key, value)?;
+        flathub.validate()?;
+        *self = flathub;
+
+        Ok(())
+    }
+
+    fn set_value(&mut self, key: Key, value: &str) -> Result<()> {
+        match key {
+            Key::OnlyArches => self.only_arches = Some(parse_arches(value)?),
+            Key::SkipArches => self.skip_arches = Some(parse_arches(value)?),
+            Key::SkipIconsCheck => self.skip_icons_check = Some(parse_bool(value)?),
+            Key::EndOfLife => {
+                if value.trim().is_empty() {
+                    return Err(anyerror!("The end of life message can't be empty"));
+                }
+                self.end_of_life = Some(value.to_string());
+            }
+            Key::EndOfLifeRebase => {
+                if !is_valid_app_id(value) {
+                    return Err(anyerror!(format!("'{value}' isn't a valid app id")));
+                }
+                self.end_of_life_rebase = Some(value.to_string());
+            }
+            Key::AutomergeFlathubbotPrs => self.automerge_flathubbot_prs = Some(parse_bool(value)?),
+            Key::DisableExternalDataChecker => {
+                self.disable_external_data_checker = Some(parse_bool(value)?)
+            }
+            Key::PublishDelayHours => {
+                self.publish_delay_hours = Some(
+                    value
+                        .parse()
+                        .map_err(|_| anyerror!(format!("'{value}' isn't a number of hours")))?,
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove `key`.
+    pub fn unset(&mut self, key: Key) {
+        match key {
+            Key::OnlyArches => self.only_arches = None,
+            Key::SkipArches => self.skip_arches = None,
+            Key::SkipIconsCheck => self.skip_icons_check = None,
+            Key::EndOfLife => self.end_of_life = None,
+            Key::EndOfLifeRebase => self.end_of_life_rebase = None,
+            Key::AutomergeFlathubbotPrs => self.automerge_flathubbot_prs = None,
+            Key::DisableExternalDataChecker => self.disable_external_data_checker = None,
+            Key::PublishDelayHours => self.publish_delay_hours = None,
+        }
+    }
+
+    /// Validate the combination of keys.
+    pub fn validate(&self) -> Result<()> {
+        if self.only_arches.is_some() && self.skip_arches.is_some() {
+            return Err(anyerror!("only-arches and skip-arches are exclusive"));
+        }
+        if self.only_arches.as_ref().is_some_and(Vec::is_empty) {
+            return Err(anyerror!("only-arches can't be empty"));
+        }
+        if self.end_of_life_rebase.is_some() && self.end_of_life.is_none() {
+            return Err(anyerror!("end-of-life-rebase requires end-of-life"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flathub_json() {
+        let mut flathub: Flathub =
+            serde_json::from_str(r#"{"skip-icons-check": true, "foo": "bar"}"#).unwrap();
+        assert_eq!(
+            flathub.get(Key::SkipIconsCheck),
+            Some(JsonValue::Bool(true))
+        );
+
+        flathub.set(Key::OnlyArches, "x86_64").unwrap();
+        assert!(flathub.set(Key::SkipArches, "aarch64").is_err());
+        assert!(flathub.set(Key::OnlyArches, "i386").is_err());
+        flathub.unset(Key::SkipIconsCheck);
+        assert!(flathub.set(Key::PublishDelayHours, "soon").is_err());
+        assert!(flathub.set(Key::EndOfLifeRebase, "org.example").is_err());
+        assert!(flathub
+            .set(Key::EndOfLifeRebase, "org.example.New")
+            .is_err());
+        flathub.set(Key::EndOfLife, "Renamed").unwrap();
+        flathub
+            .set(Key::EndOfLifeRebase, "org.example.New")
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&flathub).unwrap(),
+            r#"{"only-arches":["x86_64"],"end-of-life":"Renamed","end-of-life-rebase":"org.example.New","foo":"bar"}"#
+        );
+    }
 }
@@ -0,0 +1,336 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Application icons.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::diagnostic::Severity;
+use crate::{anyerror, error::Context, Result};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The minimum size of the largest PNG icon, without a SVG.
+const MIN_ICON_SIZE: u32 = 128;
+/// The maximum size of a PNG icon.
+const MAX_ICON_SIZE: u32 = 512;
+
+/// Kind of icon.
+#[derive(Debug, PartialEq)]
+pub(crate) enum IconKind {
+    Svg,
+    /// PNG with width and height.
+    Png(u32, u32),
+}
+
+impl IconKind {
+    /// Get the icon kind for the file at `path`, from its extension.
+    pub fn from_path(path: &Path) -> Result<IconKind> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => Ok(IconKind::Svg),
+            Some("png") => {
+                let (width, height) = png_dimensions(path)?;
+                Ok(IconKind::Png(width, height))
+            }
+            _ => Err(anyerror!(format!("{path:?} is not a SVG or PNG icon"))),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png(..) => "png",
+        }
+    }
+
+    /// The size directory in the hicolor icon theme.
+    pub fn size_dir(&self) -> String {
+        match self {
+            Self::Svg => "scalable".to_string(),
+            Self::Png(width, height) => format!("{width}x{height}"),
+        }
+    }
+
+    /// Check that the icon at `path` can be installed alone as the
+    /// application icon: a SVG, or a square PNG of at least
+    /// `MIN_ICON_SIZE`.
+    pub fn validate(&self, path: &Path) -> Result<()> {
+        match *self {
+            Self::Svg => Ok(()),
+            Self::Png(width, height) if width != height => Err(anyerror!(format!(
+                "{}: icon is {width}x{height}, it must be square",
+                path.display()
+            ))),
+            Self::Png(width, height) if width < MIN_ICON_SIZE => Err(anyerror!(format!(
+                "{}: icon is {width}x{height}, it must be at least {MIN_ICON_SIZE}x{MIN_ICON_SIZE}, or a SVG",
+                path.display()
+            ))),
+            Self::Png(..) => Ok(()),
+        }
+    }
+
+    /// The destination directory of the icon in the hicolor icon
+    /// theme, for install in the module.
+    pub fn install_dir(&self) -> String {
+        format!(
+            "${{FLATPAK_DEST}}/share/icons/hicolor/{}/apps",
+            self.size_dir()
+        )
+    }
+}
+
+/// Return the width and height of the PNG file at `path`.
+pub fn png_dimensions(path: &Path) -> Result<(u32, u32)> {
+    let mut header = [0_u8; 24];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .with_context(|| format!("Reading {path:?}"))?;
+    // The IHDR chunk is first, right after the signature.
+    if &header[0..8] != PNG_SIGNATURE || &header[12..16] != b"IHDR" {
+        return Err(anyerror!(format!("{path:?} is not a PNG file")));
+    }
+    let width = u32::from_be_bytes(header[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(header[20..24].try_into().unwrap());
+
+    Ok((width, height))
+}
+
+/// The icons of an application found in a build.
+#[derive(Default)]
+pub(crate) struct IconCheck {
+    /// The icons found.
+    pub icons: Vec<(PathBuf, IconKind)>,
+    /// The problems.
+    pub problems: Vec<(Severity, String)>,
+}
+
+impl IconCheck {
+    fn report<S: Into<String>>(&mut self, severity: Severity, message: S) {
+        self.problems.push((severity, message.into()));
+    }
+
+    /// Check the icon at `path`, in the size directory `size_dir`.
+    fn check_icon(&mut self, path: PathBuf, size_dir: &str) {
+        let kind = match IconKind::from_path(&path) {
+            Ok(kind) => kind,
+            Err(err) => {
+                self.report(Severity::Error, err.to_string());
+                return;
+            }
+        };
+        let name = path.strip_prefix(path.ancestors().nth(3).unwrap_or(Path::new("")));
+        let name = name.unwrap_or(&path).display();
+        match kind {
+            IconKind::Svg if size_dir != "scalable" => self.report(
+                Severity::Error,
+                format!("{name}: SVG icon must be in scalable"),
+            ),
+            IconKind::Svg => {}
+            IconKind::Png(width, height) => {
+                if width != height {
+                    self.report(
+                        Severity::Error,
+                        format!("{name}: icon is {width}x{height}, it must be square"),
+                    );
+                }
+                // Size directories are `SIZExSIZE` or `SIZExSIZE@SCALE`.
+                let (size, scale) = size_dir.split_once('@').unwrap_or((size_dir, "1"));
+                let scale = scale.parse::<u32>().unwrap_or(1);
+                let expected = size
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+                    .map(|(w, h)| (w * scale, h * scale));
+                if expected != Some((width, height)) {
+                    self.report(
+                        Severity::Error,
+                        format!("{name}: icon is {width}x{height}, it doesn't match the directory {size_dir}"),
+                    );
+                }
+                if width.max(height) > MAX_ICON_SIZE {
+                    self.report(
+                        Severity::Warning,
+                        format!("{name}: icon is larger than {MAX_ICON_SIZE}x{MAX_ICON_SIZE}"),
+                    );
+                }
+            }
+        }
+        self.icons.push((path, kind));
+    }
+
+    /// Whether there is a SVG or a large enough PNG.
+    pub fn has_usable_icon(&self) -> bool {
+        self.icons.iter().any(|(_, kind)| match kind {
+            IconKind::Svg => true,
+            IconKind::Png(width, height) => *width.min(height) >= MIN_ICON_SIZE,
+        })
+    }
+}
+
+/// Check the icons for the application `id` in `share_dir`, the
+/// `share` directory of the build export.
+pub(crate) fn check_icons(share_dir: &Path, id: &str) -> Result<IconCheck> {
+    let mut check = IconCheck::default();
+    let hicolor = share_dir.join("icons/hicolor");
+    let mut size_dirs = match std::fs::read_dir(&hicolor) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<std::io::Result<Vec<_>>>()
+            .with_context(|| format!("Reading {hicolor:?}"))?,
+        Err(_) => vec![],
+    };
+    size_dirs.sort();
+
+    for size_dir in size_dirs {
+        let size_dir = size_dir.to_string_lossy();
+        // Symbolic icons don't count.
+        if size_dir == "symbolic" {
+            continue;
+        }
+        let apps = hicolor.join(&*size_dir).join("apps");
+        let Ok(entries) = std::fs::read_dir(&apps) else {
+            continue;
+        };
+        let mut icons: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_stem()
+                    .is_some_and(|stem| stem.to_string_lossy() == id)
+            })
+            .collect();
+        icons.sort();
+        for icon in icons {
+            check.check_icon(icon, &size_dir);
+        }
+    }
+
+    if check.icons.is_empty() {
+        check.report(
+            Severity::Error,
+            format!("no icon {id} in share/icons/hicolor/*/apps"),
+        );
+    } else if !check.has_usable_icon() {
+        check.report(
+            Severity::Error,
+            format!("no SVG icon, or PNG icon of at least {MIN_ICON_SIZE}x{MIN_ICON_SIZE}"),
+        );
+    }
+
+    Ok(check)
+}
+
+/// The command to install the `icon` file as the icon of `id`, for
+/// the post-install of the main module.
+pub(crate) fn install_command(icon: &Path, id: &str) -> Result<String> {
+    let kind = IconKind::from_path(icon)?;
+    kind.validate(icon)?;
+
+    Ok(format!(
+        "install -Dm644 {} {}/{id}.{}",
+        icon.display(),
+        kind.install_dir(),
+        kind.extension()
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Write the header of a PNG of `width` x `height` in `path`.
+    /// This is enough for `png_dimensions`.
+    pub(crate) fn write_png_header(path: &Path, width: u32, height: u32) {
+        let mut data = PNG_SIGNATURE.to_vec();
+        data.extend_from_slice(&13_u32.to_be_bytes());
+        data.extend_from_slice(b"IHDR");
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        std::fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_icon_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = dir.path().join("icon.png");
+        write_png_header(&png, 128, 128);
+        let kind = IconKind::from_path(&png).unwrap();
+        assert_eq!(kind, IconKind::Png(128, 128));
+        assert_eq!(
+            kind.install_dir(),
+            "${FLATPAK_DEST}/share/icons/hicolor/128x128/apps"
+        );
+
+        let svg = dir.path().join("icon.svg");
+        assert_eq!(IconKind::from_path(&svg).unwrap(), IconKind::Svg);
+
+        let bogus = dir.path().join("bogus.png");
+        std::fs::write(&bogus, "not a png at all, really not").unwrap();
+        assert!(IconKind::from_path(&bogus).is_err());
+        assert!(IconKind::from_path(&dir.path().join("icon.ico")).is_err());
+    }
+
+    #[test]
+    fn test_check_icons() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = "org.example.App";
+        let hicolor = dir.path().join("icons/hicolor");
+        let add_png = |size_dir: &str, width, height| {
+            let apps = hicolor.join(size_dir).join("apps");
+            std::fs::create_dir_all(&apps).unwrap();
+            write_png_header(&apps.join(format!("{id}.png")), width, height);
+        };
+
+        let check = check_icons(dir.path(), id).unwrap();
+        assert!(check.icons.is_empty());
+        assert_eq!(check.problems.len(), 1);
+
+        add_png("64x64", 64, 64);
+        let check = check_icons(dir.path(), id).unwrap();
+        assert_eq!(
+            check.problems,
+            [(
+                Severity::Error,
+                "no SVG icon, or PNG icon of at least 128x128".to_string()
+            )]
+        );
+
+        add_png("128x128", 128, 120);
+        add_png("64x64@2", 128, 128);
+        let check = check_icons(dir.path(), id).unwrap();
+        assert_eq!(check.icons.len(), 3);
+        assert_eq!(
+            check.problems,
+            [
+                (
+                    Severity::Error,
+                    "128x128/apps/org.example.App.png: icon is 128x120, it must be square"
+                        .to_string()
+                ),
+                (
+                    Severity::Error,
+                    "128x128/apps/org.example.App.png: icon is 128x120, it doesn't match the directory 128x128"
+                        .to_string()
+                ),
+            ]
+        );
+
+        let png = dir.path().join("icon.png");
+        write_png_header(&png, 256, 256);
+        assert_eq!(
+            install_command(&png, id).unwrap(),
+            format!(
+                "install -Dm644 {} ${{FLATPAK_DEST}}/share/icons/hicolor/256x256/apps/{id}.png",
+                png.display()
+            )
+        );
+        write_png_header(&png, 256, 240);
+        assert!(install_command(&png, id).is_err());
+        write_png_header(&png, 64, 64);
+        assert!(install_command(&png, id).is_err());
+        assert_eq!(
+            install_command(&dir.path().join("icon.svg"), id).unwrap(),
+            format!("install -Dm644 {}/icon.svg ${{FLATPAK_DEST}}/share/icons/hicolor/scalable/apps/{id}.svg", dir.path().display())
+        );
+    }
+}
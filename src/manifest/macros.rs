@@ -0,0 +1,36 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Macros for the manifest.
+
+/// Implement `Display` and `FromStr` for the enum `$type`, mapping each
+/// variant to its string value.
+macro_rules! string_enum {
+    ($type:ty, $($variant:ident => $value:literal),+ $(,)?) => {
+        impl std::fmt::Display for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "{}",
+                    match *self {
+                        $(Self::$variant => $value,)+
+                    }
+                )
+            }
+        }
+
+        impl std::str::FromStr for $type {
+            type Err = String;
+
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                match s {
+                    $($value => Ok(Self::$variant),)+
+                    _ => Err(format!("unknown value '{s}'")),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use string_enum;
@@ -0,0 +1,407 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Offline checks of the metainfo.
+
+use std::cmp::Ordering;
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use super::{child, date_from_timestamp};
+use crate::manifest::diagnostic::{Diagnostic, Severity};
+
+/// Values left by `metainfo::generate` to be filled.
+const PLACEHOLDERS: &[&str] = &["CONTACT"];
+
+/// What to check the metainfo against.
+pub(crate) struct CheckContext<'a> {
+    /// The id of the manifest.
+    pub manifest_id: Option<&'a str>,
+    /// The directory where the desktop file is expected.
+    pub project_dir: &'a Path,
+}
+
+/// Compare the versions `a` and `b`. Numeric parts are compared as
+/// numbers.
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let is_separator = |c: char| matches!(c, '.' | '-' | '_' | '+' | '~');
+    let mut a_parts = a.split(is_separator);
+    let mut b_parts = b.split(is_separator);
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.cmp(b),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Return the `YYYY-MM-DD` part of the ISO 8601 `date`.
+fn parse_date(date: &str) -> Option<&str> {
+    let day = date.get(0..10)?;
+    let valid = day.char_indices().all(|(idx, c)| match idx {
+        4 | 7 => c == '-',
+        _ => c.is_ascii_digit(),
+    });
+
+    valid.then_some(day)
+}
+
+/// The trimmed text of `node`.
+fn text<'a>(node: Node<'a, '_>) -> &'a str {
+    node.text().unwrap_or_default().trim()
+}
+
+struct Checker<'a, 'input> {
+    doc: &'a Document<'input>,
+    context: &'a CheckContext<'a>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a, 'input> Checker<'a, 'input> {
+    fn report<S: Into<String>>(&mut self, node: Node, severity: Severity, message: S) {
+        let pos = self.doc.text_pos_at(node.range().start);
+        self.diagnostics.push(Diagnostic {
+            line: pos.row,
+            column: pos.col,
+            severity,
+            message: message.into(),
+        });
+    }
+
+    /// Check that the element `name` is present with some text.
+    fn check_text_element(&mut self, component: Node<'a, 'input>, name: &str) {
+        match child(component, name) {
+            None => self.report(component, Severity::Error, format!("missing <{name}>")),
+            Some(node) if text(node).is_empty() => {
+                self.report(node, Severity::Error, format!("empty <{name}>"))
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn check_id(&mut self, component: Node<'a, 'input>) {
+        self.check_text_element(component, "id");
+        if let (Some(node), Some(manifest_id)) = (child(component, "id"), self.context.manifest_id)
+        {
+            let id = text(node);
+            if !id.is_empty() && id != manifest_id {
+                self.report(
+                    node,
+                    Severity::Error,
+                    format!("id '{id}' doesn't match the manifest id '{manifest_id}'"),
+                );
+            }
+        }
+    }
+
+    fn check_developer(&mut self, component: Node<'a, 'input>) {
+        match child(component, "developer") {
+            None => self.report(component, Severity::Error, "missing <developer>"),
+            Some(developer) => self.check_text_element(developer, "name"),
+        }
+    }
+
+    fn check_launchable(&mut self, component: Node<'a, 'input>) {
+        let launchable = component.children().find(|n| {
+            n.is_element()
+                && n.tag_name().name() == "launchable"
+                && n.attribute("type") == Some("desktop-id")
+        });
+        let Some(launchable) = launchable else {
+            self.report(
+                component,
+                Severity::Error,
+                "missing <launchable type=\"desktop-id\">",
+            );
+            return;
+        };
+        let desktop_id = text(launchable);
+        if desktop_id.is_empty() {
+            self.report(launchable, Severity::Error, "empty <launchable>");
+        } else if !self.context.project_dir.join(desktop_id).is_file() {
+            self.report(
+                launchable,
+                Severity::Error,
+                format!("launchable desktop file '{desktop_id}' not found"),
+            );
+        }
+    }
+
+    fn check_screenshots(&mut self, component: Node<'a, 'input>) {
+        let Some(screenshots) = child(component, "screenshots") else {
+            self.report(component, Severity::Error, "missing <screenshots>");
+            return;
+        };
+        let screenshots: Vec<_> = screenshots
+            .children()
+            .filter(|n| n.is_element() && n.tag_name().name() == "screenshot")
+            .collect();
+        if screenshots.is_empty() {
+            self.report(component, Severity::Error, "no <screenshot>");
+        }
+        for screenshot in screenshots {
+            self.check_text_element(screenshot, "image");
+        }
+    }
+
+    fn check_urls(&mut self, component: Node<'a, 'input>) {
+        let urls: Vec<_> = component
+            .children()
+            .filter(|n| n.is_element() && n.tag_name().name() == "url")
+            .collect();
+        if !urls.iter().any(|n| n.attribute("type") == Some("homepage")) {
+            self.report(
+                component,
+                Severity::Warning,
+                "missing <url type=\"homepage\">",
+            );
+        }
+        for url in urls {
+            if text(url).is_empty() {
+                let url_type = url.attribute("type").unwrap_or_default();
+                self.report(
+                    url,
+                    Severity::Error,
+                    format!("empty <url type=\"{url_type}\">"),
+                );
+            }
+        }
+    }
+
+    /// Return the date of the `release`, from the `date` or `timestamp`
+    /// attributes.
+    fn release_date(&mut self, release: Node<'a, 'input>) -> Option<String> {
+        if let Some(date) = release.attribute("date") {
+            let day = parse_date(date).map(str::to_string);
+            if day.is_none() {
+                self.report(release, Severity::Error, format!("invalid date '{date}'"));
+            }
+            return day;
+        }
+        if let Some(timestamp) = release.attribute("timestamp") {
+            let day = timestamp.parse::<i64>().ok().map(date_from_timestamp);
+            if day.is_none() {
+                self.report(
+                    release,
+                    Severity::Error,
+                    format!("invalid timestamp '{timestamp}'"),
+                );
+            }
+            return day;
+        }
+        self.report(release, Severity::Error, "release without a date");
+
+        None
+    }
+
+    fn check_releases(&mut self, component: Node<'a, 'input>) {
+        let Some(releases) = child(component, "releases") else {
+            self.report(component, Severity::Error, "missing <releases>");
+            return;
+        };
+        let releases: Vec<_> = releases
+            .children()
+            .filter(|n| n.is_element() && n.tag_name().name() == "release")
+            .collect();
+        if releases.is_empty() {
+            self.report(component, Severity::Error, "no <release>");
+        }
+
+        let mut previous: Option<(&str, Option<String>)> = None;
+        for release in releases {
+            let date = self.release_date(release);
+            let Some(version) = release.attribute("version") else {
+                self.report(release, Severity::Error, "release without a version");
+                continue;
+            };
+            if let Some((previous_version, previous_date)) = &previous {
+                if compare_versions(version, previous_version) != Ordering::Less {
+                    self.report(
+                        release,
+                        Severity::Error,
+                        format!(
+                            "release {version} should be before {previous_version}: releases must be in descending order"
+                        ),
+                    );
+                }
+                if let (Some(date), Some(previous_date)) = (&date, previous_date) {
+                    if date > previous_date {
+                        self.report(
+                            release,
+                            Severity::Error,
+                            format!(
+                                "release {version} dated {date} is newer than {previous_version} dated {previous_date}"
+                            ),
+                        );
+                    }
+                }
+            }
+            previous = Some((version, date));
+        }
+    }
+
+    fn check_placeholders(&mut self, component: Node<'a, 'input>) {
+        for node in component.descendants().filter(|n| n.is_text()) {
+            let value = text(node);
+            if PLACEHOLDERS.contains(&value) {
+                let parent = node.parent().unwrap_or(node);
+                self.report(
+                    parent,
+                    Severity::Error,
+                    format!(
+                        "placeholder value '{value}' in <{}>",
+                        parent.tag_name().name()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Check the metainfo `xml`. Return the diagnostics, sorted by line.
+pub(crate) fn check(xml: &str, context: &CheckContext) -> Vec<Diagnostic> {
+    let doc = match Document::parse(xml) {
+        Ok(doc) => doc,
+        Err(err) => {
+            let pos = err.pos();
+            return vec![Diagnostic {
+                line: pos.row,
+                column: pos.col,
+                severity: Severity::Error,
+                message: err.to_string(),
+            }];
+        }
+    };
+
+    let mut checker = Checker {
+        doc: &doc,
+        context,
+        diagnostics: vec![],
+    };
+    let component = doc.root_element();
+    if component.tag_name().name() != "component" {
+        checker.report(
+            component,
+            Severity::Error,
+            "the root element must be <component>",
+        );
+        return checker.diagnostics;
+    }
+    let is_application = component.attribute("type") == Some("desktop-application");
+
+    checker.check_id(component);
+    for name in ["name", "summary", "metadata_license", "project_license"] {
+        checker.check_text_element(component, name);
+    }
+    if is_application {
+        checker.check_developer(component);
+        checker.check_launchable(component);
+        checker.check_screenshots(component);
+    }
+    checker.check_urls(component);
+    checker.check_releases(component);
+    checker.check_placeholders(component);
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("2.0-beta", "2.0-alpha"), Ordering::Greater);
+        assert_eq!(compare_versions("3.2", "3.2"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_check() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("org.example.App.desktop"), "").unwrap();
+        let context = CheckContext {
+            manifest_id: Some("org.example.App"),
+            project_dir: dir.path(),
+        };
+
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+  <id>org.example.App</id>
+  <name>App</name>
+  <summary>An app</summary>
+  <metadata_license>CC0-1.0</metadata_license>
+  <project_license>GPL-3.0-or-later</project_license>
+  <developer id="org.example"><name>Example</name></developer>
+  <launchable type="desktop-id">org.example.App.desktop</launchable>
+  <url type="homepage">https://example.org</url>
+  <screenshots>
+    <screenshot type="default"><image>https://example.org/1.png</image></screenshot>
+  </screenshots>
+  <releases>
+    <release version="1.10" date="2026-02-01"/>
+    <release version="1.9" date="2025-12-24"/>
+  </releases>
+</component>
+"#;
+        assert!(check(xml, &context).is_empty());
+
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+  <id>org.example.Other</id>
+  <name>App</name>
+  <summary/>
+  <metadata_license>CC0-1.0</metadata_license>
+  <project_license/>
+  <update_contact>CONTACT</update_contact>
+  <launchable type="desktop-id">org.example.Other.desktop</launchable>
+  <url type="homepage"/>
+  <releases>
+    <release version="1.9" date="2025-12-24"/>
+    <release version="1.10" date="2026-02-01"/>
+    <release version="1.0"/>
+  </releases>
+</component>
+"#;
+        let diagnostics: Vec<String> = check(xml, &context)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "2:1: error: missing <developer>",
+                "2:1: error: missing <screenshots>",
+                "3:3: error: id 'org.example.Other' doesn't match the manifest id 'org.example.App'",
+                "5:3: error: empty <summary>",
+                "7:3: error: empty <project_license>",
+                "8:3: error: placeholder value 'CONTACT' in <update_contact>",
+                "9:3: error: launchable desktop file 'org.example.Other.desktop' not found",
+                "10:3: error: empty <url type=\"homepage\">",
+                "13:5: error: release 1.10 should be before 1.9: releases must be in descending order",
+                "13:5: error: release 1.10 dated 2026-02-01 is newer than 1.9 dated 2025-12-24",
+                "14:5: error: release without a date",
+            ]
+        );
+
+        let diagnostics = check("<component>", &context);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+}
@@ -0,0 +1,417 @@
+// SPDX-FileCopyrightText: 2020-2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+pub(crate) mod check;
+pub(crate) mod release;
+pub(crate) mod rename;
+
+use std::collections::BTreeMap;
+use std::io::prelude::*;
+use std::path::Path;
+
+use xmlwriter::*;
+
+use super::tui::Ui;
+use super::Answers;
+use crate::{
+    anyerror,
+    manifest::{Manifest, PackageType},
+    Result,
+};
+
+/// The URL types allowed by AppStream.
+const URL_TYPES: &[&str] = &[
+    "homepage",
+    "bugtracker",
+    "faq",
+    "help",
+    "donation",
+    "translate",
+    "contact",
+    "vcs-browser",
+    "contribute",
+];
+
+/// The values of an OARS content attribute.
+const OARS_VALUES: &[&str] = &["none", "mild", "moderate", "intense"];
+
+/// The metainfo content of an application.
+#[derive(Debug, Default)]
+pub(crate) struct AppMetainfo {
+    /// The name. Default to the short id.
+    pub name: Option<String>,
+    pub summary: Option<String>,
+    /// The description. Paragraphs are separated by an empty line.
+    pub description: Option<String>,
+    pub developer_id: Option<String>,
+    pub developer_name: Option<String>,
+    /// The SPDX project license.
+    pub project_license: Option<String>,
+    /// The URLs by type.
+    pub urls: BTreeMap<String, String>,
+    /// The OARS 1.1 content attributes. Those not listed are `none`.
+    pub content_rating: BTreeMap<String, String>,
+    /// The primary brand colors by scheme preference, `light` or `dark`.
+    pub branding: BTreeMap<String, String>,
+    /// The screenshots image URLs. The first is the default.
+    pub screenshots: Vec<String>,
+    /// The version of the first release.
+    pub release: Option<String>,
+}
+
+/// Return the non empty answer to `prompt`.
+fn prompt_text(ui: &mut dyn Ui, prompt: &str) -> Option<String> {
+    ui.input_optional(prompt).filter(|s| !s.is_empty())
+}
+
+/// Whether `color` is a `#rrggbb` color.
+fn is_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+impl AppMetainfo {
+    /// Create the metainfo from the `answers`, prompting with `ui`
+    /// for the most important missing values. These are all
+    /// optional.
+    pub fn prompt_with_answers(answers: &Answers, ui: &mut dyn Ui) -> Result<AppMetainfo> {
+        let metainfo = AppMetainfo {
+            name: answers.name.clone(),
+            summary: answers
+                .summary
+                .clone()
+                .or_else(|| prompt_text(ui, "Summary")),
+            description: answers
+                .description
+                .clone()
+                .or_else(|| prompt_text(ui, "Description")),
+            developer_id: answers.developer_id.clone(),
+            developer_name: answers
+                .developer_name
+                .clone()
+                .or_else(|| prompt_text(ui, "Developer name")),
+            project_license: answers
+                .license
+                .clone()
+                .or_else(|| prompt_text(ui, "Project license (SPDX)")),
+            urls: match answers.urls {
+                Some(ref urls) => urls.clone(),
+                None => prompt_text(ui, "Homepage URL")
+                    .map(|url| BTreeMap::from([("homepage".to_string(), url)]))
+                    .unwrap_or_default(),
+            },
+            content_rating: answers.content_rating.clone().unwrap_or_default(),
+            branding: answers.branding.clone().unwrap_or_default(),
+            screenshots: match answers.screenshots {
+                Some(ref screenshots) => screenshots.clone(),
+                None => {
+                    let mut screenshots = vec![];
+                    while ui.confirm("Add a screenshot?", screenshots.is_empty()) == Some(true) {
+                        match prompt_text(ui, "Screenshot image URL") {
+                            Some(url) => screenshots.push(url),
+                            None => break,
+                        }
+                    }
+                    screenshots
+                }
+            },
+            release: answers
+                .release
+                .clone()
+                .or_else(|| prompt_text(ui, "First release version")),
+        };
+        metainfo.validate()?;
+
+        Ok(metainfo)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(url_type) = self.urls.keys().find(|t| !URL_TYPES.contains(&t.as_str())) {
+            return Err(anyerror!(format!("Invalid URL type '{url_type}'")));
+        }
+        if let Some((id, value)) = self
+            .content_rating
+            .iter()
+            .find(|(_, value)| !OARS_VALUES.contains(&value.as_str()))
+        {
+            return Err(anyerror!(format!(
+                "Invalid content rating value '{value}' for '{id}'"
+            )));
+        }
+        for (scheme, color) in &self.branding {
+            if scheme != "light" && scheme != "dark" {
+                return Err(anyerror!(format!("Invalid branding scheme '{scheme}'")));
+            }
+            if !is_color(color) {
+                return Err(anyerror!(format!("Invalid branding color '{color}'")));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Return today's date as `YYYY-MM-DD` (UTC).
+pub(crate) fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    date_from_timestamp(secs as i64)
+}
+
+/// The `YYYY-MM-DD` date of the Unix timestamp `secs`, in UTC.
+pub(crate) fn date_from_timestamp(secs: i64) -> String {
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Convert the number of days since the epoch to a date.
+/// From Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Return the first child element of `node` named `name`.
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// Escape `text` for XML text or attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Return the whitespace indenting the line up to `pos` in `text`,
+/// or an empty string if something else precedes.
+fn line_indent(text: &str, pos: usize) -> &str {
+    let line_start = text[..pos].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let prefix = &text[line_start..pos];
+    if prefix.trim().is_empty() {
+        prefix
+    } else {
+        ""
+    }
+}
+
+/// Write the element `name` with `text`.
+fn write_text_element(w: &mut XmlWriter, name: &str, text: &str) {
+    w.start_element(name);
+    w.set_preserve_whitespaces(true);
+    w.write_text(text);
+    w.end_element();
+    w.set_preserve_whitespaces(false);
+}
+
+/// Generate the metainfo file for the package and return its path.
+pub fn generate(manifest: &Manifest, dest_dir: &Path) -> Result<std::path::PathBuf> {
+    let mut metainfo_file = dest_dir.to_path_buf();
+    metainfo_file.push(format!("{}.metainfo.xml", &manifest.id));
+
+    let mut file = std::fs::File::create(&metainfo_file)?;
+
+    let content = if matches!(manifest.package_type, PackageType::Application) {
+        generate_application(manifest)
+    } else {
+        generate_addon(manifest)
+    };
+    file.write_all(content.as_bytes())?;
+
+    Ok(metainfo_file)
+}
+
+fn generate_addon(manifest: &Manifest) -> String {
+    let mut w = XmlWriter::new(Options::default());
+    w.write_declaration();
+
+    w.start_element("component");
+    w.write_attribute("type", "addon");
+
+    write_text_element(&mut w, "id", &manifest.id);
+    write_text_element(&mut w, "extends", manifest.runtime.to_string_name());
+    write_text_element(&mut w, "name", &manifest.short_id);
+    write_text_element(&mut w, "summary", &manifest.short_id);
+
+    w.start_element("project_license");
+    w.end_element();
+
+    write_text_element(&mut w, "metadata_license", "CC0-1.0");
+    write_text_element(&mut w, "update_contact", "CONTACT");
+
+    w.start_element("url");
+    w.write_attribute("type", "homepage");
+    w.end_element();
+
+    w.start_element("releases");
+    w.end_element();
+
+    w.end_document()
+}
+
+fn generate_application(manifest: &Manifest) -> String {
+    let metainfo = &manifest.metainfo;
+    let mut w = XmlWriter::new(Options::default());
+    w.write_declaration();
+
+    w.start_element("component");
+    w.write_attribute("type", "desktop-application");
+
+    write_text_element(&mut w, "id", &manifest.id);
+    write_text_element(
+        &mut w,
+        "name",
+        metainfo.name.as_deref().unwrap_or(&manifest.short_id),
+    );
+    write_text_element(
+        &mut w,
+        "summary",
+        metainfo.summary.as_deref().unwrap_or(&manifest.short_id),
+    );
+    write_text_element(&mut w, "metadata_license", "CC0-1.0");
+    // Missing values are left out rather than written empty, for
+    // `metainfo check` to report them.
+    if let Some(ref license) = metainfo.project_license {
+        write_text_element(&mut w, "project_license", license);
+    }
+
+    if metainfo.developer_id.is_some() || metainfo.developer_name.is_some() {
+        w.start_element("developer");
+        if let Some(ref id) = metainfo.developer_id {
+            w.write_attribute("id", id);
+        }
+        if let Some(ref name) = metainfo.developer_name {
+            write_text_element(&mut w, "name", name);
+        }
+        w.end_element();
+    }
+
+    if let Some(ref description) = metainfo.description {
+        w.start_element("description");
+        description
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .for_each(|p| write_text_element(&mut w, "p", p));
+        w.end_element();
+    }
+
+    w.start_element("launchable");
+    w.write_attribute("type", "desktop-id");
+    w.set_preserve_whitespaces(true);
+    w.write_text(&format!("{}.desktop", &manifest.id));
+    w.end_element();
+    w.set_preserve_whitespaces(false);
+
+    for (url_type, url) in &metainfo.urls {
+        w.start_element("url");
+        w.write_attribute("type", url_type);
+        w.set_preserve_whitespaces(true);
+        w.write_text(url);
+        w.end_element();
+        w.set_preserve_whitespaces(false);
+    }
+
+    if !metainfo.branding.is_empty() {
+        w.start_element("branding");
+        for (scheme, color) in &metainfo.branding {
+            w.start_element("color");
+            w.write_attribute("type", "primary");
+            w.write_attribute("scheme_preference", scheme);
+            w.set_preserve_whitespaces(true);
+            w.write_text(color);
+            w.end_element();
+            w.set_preserve_whitespaces(false);
+        }
+        w.end_element();
+    }
+
+    w.start_element("screenshots");
+    for (idx, screenshot) in metainfo.screenshots.iter().enumerate() {
+        w.start_element("screenshot");
+        if idx == 0 {
+            w.write_attribute("type", "default");
+        }
+        write_text_element(&mut w, "image", screenshot);
+        w.end_element();
+    }
+    w.end_element();
+
+    w.start_element("content_rating");
+    w.write_attribute("type", "oars-1.1");
+    for (id, value) in &metainfo.content_rating {
+        w.start_element("content_attribute");
+        w.write_attribute("id", id);
+        w.set_preserve_whitespaces(true);
+        w.write_text(value);
+        w.end_element();
+        w.set_preserve_whitespaces(false);
+    }
+    w.end_element();
+
+    // Without a version, the release is left to `release add`.
+    w.start_element("releases");
+    if let Some(ref version) = metainfo.release {
+        w.start_element("release");
+        w.write_attribute("version", version);
+        w.write_attribute("date", &today());
+        w.end_element();
+    }
+    w.end_element();
+
+    w.end_document()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20745), (2026, 10, 19));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut metainfo = AppMetainfo {
+            urls: BTreeMap::from([("homepage".to_string(), "https://example.org".to_string())]),
+            content_rating: BTreeMap::from([("social-chat".to_string(), "intense".to_string())]),
+            branding: BTreeMap::from([("light".to_string(), "#faa0a0".to_string())]),
+            ..AppMetainfo::default()
+        };
+        assert!(metainfo.validate().is_ok());
+
+        metainfo
+            .branding
+            .insert("dark".to_string(), "red".to_string());
+        assert!(metainfo.validate().is_err());
+        metainfo.branding.clear();
+
+        metainfo
+            .urls
+            .insert("website".to_string(), "https://example.org".to_string());
+        assert!(metainfo.validate().is_err());
+    }
+}
@@ -0,0 +1,239 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Releases in the metainfo.
+
+use std::cmp::Ordering;
+
+use roxmltree::Document;
+
+use super::check::compare_versions;
+use super::{child, escape, line_indent};
+use crate::{anyerror, Result};
+
+/// A release to add.
+pub(crate) struct NewRelease {
+    pub version: String,
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    /// Plain text description. Paragraphs are separated by an empty
+    /// line, and lines starting with `- ` or `* ` are list items.
+    pub description: Option<String>,
+}
+
+/// Convert the plain text `description` into markup lines, relative
+/// to the description element.
+fn description_markup(description: &str, unit: &str) -> Vec<String> {
+    let mut lines = vec![];
+    for block in description.split("\n\n") {
+        let mut paragraph: Vec<&str> = vec![];
+        let mut items: Vec<String> = vec![];
+        for line in block.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            match line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+                Some(item) => items.push(item.trim().to_string()),
+                // Continuation of the previous item.
+                None if !items.is_empty() => {
+                    if let Some(item) = items.last_mut() {
+                        item.push(' ');
+                        item.push_str(line);
+                    }
+                }
+                None => paragraph.push(line),
+            }
+        }
+        if !paragraph.is_empty() {
+            lines.push(format!("<p>{}</p>", escape(&paragraph.join(" "))));
+        }
+        if !items.is_empty() {
+            lines.push("<ul>".to_string());
+            lines.extend(
+                items
+                    .iter()
+                    .map(|item| format!("{unit}<li>{}</li>", escape(item))),
+            );
+            lines.push("</ul>".to_string());
+        }
+    }
+
+    lines
+}
+
+impl NewRelease {
+    /// The markup for the release. The first line isn't indented,
+    /// the other lines are indented with `indent`, and `unit` for each
+    /// level.
+    fn markup(&self, indent: &str, unit: &str) -> String {
+        let start = format!(
+            "<release version=\"{}\" date=\"{}\"",
+            escape(&self.version),
+            escape(&self.date)
+        );
+        let description = self
+            .description
+            .as_deref()
+            .map(|description| description_markup(description, unit))
+            .filter(|lines| !lines.is_empty());
+        match description {
+            None => format!("{start}/>"),
+            Some(lines) => {
+                let mut markup = format!("{start}>\n{indent}{unit}<description>\n");
+                for line in lines {
+                    markup.push_str(&format!("{indent}{unit}{unit}{line}\n"));
+                }
+                markup.push_str(&format!("{indent}{unit}</description>\n{indent}</release>"));
+                markup
+            }
+        }
+    }
+}
+
+/// Add the `release` to the metainfo `xml`, keeping the releases in
+/// descending order and the formatting of the file.
+pub(crate) fn add_release(xml: &str, release: &NewRelease) -> Result<String> {
+    let doc = Document::parse(xml).map_err(|err| anyerror!(format!("Invalid metainfo: {err}")))?;
+    let releases = child(doc.root_element(), "releases")
+        .ok_or_else(|| anyerror!("No <releases> in the metainfo"))?;
+    let existing: Vec<_> = releases
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().name() == "release")
+        .collect();
+    if existing
+        .iter()
+        .any(|r| r.attribute("version") == Some(release.version.as_str()))
+    {
+        return Err(anyerror!(format!(
+            "Release {} already exists",
+            release.version
+        )));
+    }
+
+    let releases_indent = line_indent(xml, releases.range().start);
+    let (indent, unit) = match existing.first() {
+        Some(first) => {
+            let indent = line_indent(xml, first.range().start);
+            let unit = indent
+                .strip_prefix(releases_indent)
+                .filter(|unit| !unit.is_empty())
+                .unwrap_or("  ");
+            (indent.to_string(), unit.to_string())
+        }
+        None => (format!("{releases_indent}  "), "  ".to_string()),
+    };
+    let markup = release.markup(&indent, &unit);
+
+    let older = existing.iter().find(|r| {
+        r.attribute("version")
+            .is_some_and(|v| compare_versions(v, &release.version) == Ordering::Less)
+    });
+    let mut result = xml.to_string();
+    match (older, existing.last()) {
+        (Some(older), _) => {
+            result.insert_str(older.range().start, &format!("{markup}\n{indent}"));
+        }
+        (None, Some(last)) => {
+            result.insert_str(last.range().end, &format!("\n{indent}{markup}"));
+        }
+        (None, None) => {
+            result.replace_range(
+                releases.range(),
+                &format!("<releases>\n{indent}{markup}\n{releases_indent}</releases>"),
+            );
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METAINFO: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+    <id>org.example.App</id>
+    <releases>
+        <release version="1.10" date="2026-02-01"/>
+        <release version="1.8" date="2025-12-24">
+            <description><p>Christmas</p></description>
+        </release>
+    </releases>
+</component>
+"#;
+
+    #[test]
+    fn test_add_release() {
+        let release = NewRelease {
+            version: "1.9".to_string(),
+            date: "2026-01-10".to_string(),
+            description: Some("Bug fixes & more:\n\n- Fix crash\n- Faster".to_string()),
+        };
+        assert_eq!(
+            add_release(METAINFO, &release).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+    <id>org.example.App</id>
+    <releases>
+        <release version="1.10" date="2026-02-01"/>
+        <release version="1.9" date="2026-01-10">
+            <description>
+                <p>Bug fixes &amp; more:</p>
+                <ul>
+                    <li>Fix crash</li>
+                    <li>Faster</li>
+                </ul>
+            </description>
+        </release>
+        <release version="1.8" date="2025-12-24">
+            <description><p>Christmas</p></description>
+        </release>
+    </releases>
+</component>
+"#
+        );
+
+        let release = NewRelease {
+            version: "2.0".to_string(),
+            date: "2026-10-19".to_string(),
+            description: None,
+        };
+        let xml = add_release(METAINFO, &release).unwrap();
+        assert!(xml.contains(
+            "<releases>\n        <release version=\"2.0\" date=\"2026-10-19\"/>\n        <release version=\"1.10\""
+        ));
+
+        let release = NewRelease {
+            version: "1.0".to_string(),
+            date: "2025-01-01".to_string(),
+            description: None,
+        };
+        let xml = add_release(METAINFO, &release).unwrap();
+        assert!(xml.contains(
+            "        </release>\n        <release version=\"1.0\" date=\"2025-01-01\"/>\n    </releases>"
+        ));
+
+        assert!(add_release(
+            METAINFO,
+            &NewRelease {
+                version: "1.8".to_string(),
+                date: "2026-10-19".to_string(),
+                description: None,
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_add_first_release() {
+        let xml = "<component>\n  <id>org.example.App</id>\n  <releases/>\n</component>";
+        let release = NewRelease {
+            version: "0.1".to_string(),
+            date: "2026-10-19".to_string(),
+            description: None,
+        };
+        assert_eq!(
+            add_release(xml, &release).unwrap(),
+            "<component>\n  <id>org.example.App</id>\n  <releases>\n    <release version=\"0.1\" date=\"2026-10-19\"/>\n  </releases>\n</component>"
+        );
+    }
+}
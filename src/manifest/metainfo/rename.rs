@@ -0,0 +1,107 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Renamed applications in the metainfo.
+
+use roxmltree::Document;
+
+use super::{child, escape, line_indent};
+use crate::{anyerror, Result};
+
+/// Add `<id>` with `old_id` to the element `name` of the component,
+/// creating it if needed.
+fn add_id_to(xml: &str, name: &str, old_id: &str) -> Result<String> {
+    let doc = Document::parse(xml).map_err(|err| anyerror!(format!("Invalid metainfo: {err}")))?;
+    let component = doc.root_element();
+    let component_indent = line_indent(xml, component.range().start);
+    let (indent, unit) = match component.children().find(|n| n.is_element()) {
+        Some(first) => {
+            let indent = line_indent(xml, first.range().start);
+            let unit = indent
+                .strip_prefix(component_indent)
+                .filter(|unit| !unit.is_empty())
+                .unwrap_or("  ");
+            (indent.to_string(), unit.to_string())
+        }
+        None => (format!("{component_indent}  "), "  ".to_string()),
+    };
+    let id = format!("<id>{}</id>", escape(old_id));
+
+    let mut result = xml.to_string();
+    match child(component, name) {
+        Some(element) => {
+            let has_id = element.children().any(|n| {
+                n.is_element()
+                    && n.tag_name().name() == "id"
+                    && n.text().map(str::trim) == Some(old_id)
+            });
+            if has_id {
+                return Ok(result);
+            }
+            match element.children().rfind(|n| n.is_element()) {
+                Some(last) => {
+                    result.insert_str(last.range().end, &format!("\n{indent}{unit}{id}"));
+                }
+                None => result.replace_range(
+                    element.range(),
+                    &format!("<{name}>\n{indent}{unit}{id}\n{indent}</{name}>"),
+                ),
+            }
+        }
+        None => {
+            let element = format!("<{name}>\n{indent}{unit}{id}\n{indent}</{name}>");
+            match component.children().rfind(|n| n.is_element()) {
+                Some(last) => result.insert_str(last.range().end, &format!("\n{indent}{element}")),
+                None => {
+                    let end =
+                        component.range().end - format!("</{}>", component.tag_name().name()).len();
+                    result.insert_str(end, &format!("{indent}{element}\n{component_indent}"));
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Add `old_id` to the `<replaces>` and `<provides>` of the metainfo
+/// `xml`, for an application renamed from `old_id`.
+pub(crate) fn add_renamed_from(xml: &str, old_id: &str) -> Result<String> {
+    let xml = add_id_to(xml, "replaces", old_id)?;
+
+    add_id_to(&xml, "provides", old_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_renamed_from() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+    <id>org.example.New</id>
+    <provides>
+        <binary>app</binary>
+    </provides>
+</component>
+"#;
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+    <id>org.example.New</id>
+    <provides>
+        <binary>app</binary>
+        <id>org.example.Old</id>
+    </provides>
+    <replaces>
+        <id>org.example.Old</id>
+    </replaces>
+</component>
+"#;
+        let xml = add_renamed_from(xml, "org.example.Old").unwrap();
+        assert_eq!(xml, expected);
+        // Already there.
+        assert_eq!(add_renamed_from(&xml, "org.example.Old").unwrap(), expected);
+    }
+}
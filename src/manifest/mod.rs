@@ -2,22 +2,37 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+mod answers;
 mod config;
-mod flathub;
-mod metainfo;
-mod module;
+pub(crate) mod desktop;
+pub(crate) mod diagnostic;
+pub(crate) mod file;
+mod finish_args;
+pub(crate) mod flathub;
+pub(crate) mod icon;
+mod macros;
+pub(crate) mod metainfo;
+pub(crate) mod module;
+pub(crate) mod pin;
 mod sdk_extension;
+pub(crate) mod shared_modules;
+mod template;
+pub(crate) mod tree;
 pub(crate) mod tui;
 
-use dialoguer::Input;
 use serde::Serialize;
 use serde_json::json;
 
-use crate::{repo, Result};
-use module::ModuleEntry;
-use tui::Prompt;
+use crate::{anyerror, repo, Error, Result};
+use finish_args::FinishArgs;
+use icon::IconKind;
+use metainfo::AppMetainfo;
+use module::{Buildsystem, Module, ModuleEntry};
+use tui::{Prompt, Ui};
 
+pub(crate) use answers::Answers;
 pub(crate) use sdk_extension::SdkExtension;
+pub(crate) use template::Template;
 
 #[derive(Debug, Default)]
 pub(crate) enum PackageType {
@@ -38,6 +53,27 @@ impl PackageType {
     }
 }
 
+impl std::str::FromStr for PackageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "application" => Ok(Self::Application),
+            "linux-audio-plugin" => Ok(Self::LinuxAudioPlugin),
+            "gimp-plugin" => Ok(Self::GimpPlugin),
+            _ => Err(format!("unknown package type '{s}'")),
+        }
+    }
+}
+
+/// Split `NAME//VERSION` into its parts.
+fn split_version(s: &str) -> (&str, Option<String>) {
+    match s.split_once("//") {
+        Some((name, version)) => (name, Some(version.to_string())),
+        None => (s, None),
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum Sdk {
     Freedesktop(String),
@@ -63,6 +99,33 @@ impl std::fmt::Display for Sdk {
     }
 }
 
+impl std::str::FromStr for Sdk {
+    type Err = String;
+
+    /// Parse `NAME[//VERSION]`, `NAME` being one of `freedesktop`,
+    /// `gnome`, `kde` or `qt6`.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, version) = split_version(s);
+        match name {
+            "freedesktop" => {
+                Ok(Sdk::Freedesktop(version.unwrap_or_else(|| {
+                    config::FREEDESKTOP_VERSION.to_string()
+                })))
+            }
+            "gnome" => Ok(Sdk::Gnome(
+                version.unwrap_or_else(|| config::GNOME_VERSION.to_string()),
+            )),
+            "kde" => Ok(Sdk::Kde(
+                version.unwrap_or_else(|| config::KDE_VERSION.to_string()),
+            )),
+            "qt6" => Ok(Sdk::Qt6(
+                version.unwrap_or_else(|| config::QT6_VERSION.to_string()),
+            )),
+            _ => Err(format!("unknown SDK '{s}'")),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub enum Runtime {
     #[default]
@@ -80,6 +143,36 @@ impl std::fmt::Display for Runtime {
     }
 }
 
+impl std::str::FromStr for Runtime {
+    type Err = String;
+
+    /// Parse `NAME[//VERSION]`, `NAME` being one of `freedesktop`,
+    /// `gnome`, `kde` or `qt6`. Any other runtime requires the version.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, version) = split_version(s);
+        match name {
+            "freedesktop" => {
+                Ok(Runtime::Freedesktop(version.unwrap_or_else(|| {
+                    config::FREEDESKTOP_VERSION.to_string()
+                })))
+            }
+            "gnome" => Ok(Runtime::Gnome(
+                version.unwrap_or_else(|| config::GNOME_VERSION.to_string()),
+            )),
+            "kde" => Ok(Runtime::Kde(
+                version.unwrap_or_else(|| config::KDE_VERSION.to_string()),
+            )),
+            "qt6" => Ok(Runtime::Qt6(
+                version.unwrap_or_else(|| config::QT6_VERSION.to_string()),
+            )),
+            "" => Err("empty runtime".to_string()),
+            _ => version
+                .map(|version| Runtime::Other(name.to_string(), version))
+                .ok_or_else(|| format!("runtime '{s}' requires a version")),
+        }
+    }
+}
+
 impl Runtime {
     pub fn to_string_name(&self) -> &str {
         match self {
@@ -113,6 +206,18 @@ pub struct Manifest {
     pub(crate) sdk: Sdk,
     pub sdk_extensions: Vec<SdkExtension>,
     pub(crate) modules: Vec<ModuleEntry>,
+    pub(crate) finish_args: FinishArgs,
+    /// The command of an application.
+    pub command: Option<String>,
+    /// The icon file of an application.
+    pub icon: Option<std::path::PathBuf>,
+    /// The desktop categories of an application.
+    pub categories: Vec<String>,
+    /// The metainfo of an application.
+    pub(crate) metainfo: AppMetainfo,
+    pub build_options: serde_json::Map<String, serde_json::Value>,
+    /// Extra files to create, from the template.
+    files: Vec<template::TemplateFile>,
     need_shared_modules: bool,
 }
 
@@ -132,11 +237,93 @@ impl Manifest {
         }
     }
 
+    /// The variables for the template.
+    fn template_vars(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("id", self.id.clone()),
+            ("short_id", self.short_id.clone()),
+            ("runtime", self.runtime.to_string()),
+            ("prefix", self.get_prefix()),
+        ]
+    }
+
+    /// Apply the template found at `path`. The default modules
+    /// of the template are added before the existing modules.
+    pub fn apply_template(&mut self, path: &std::path::Path) -> Result<()> {
+        let template = Template::instantiate(path, &self.template_vars())?;
+
+        self.need_shared_modules |= template
+            .modules
+            .iter()
+            .any(|module| matches!(module, ModuleEntry::SharedModule(_)));
+        let modules = std::mem::take(&mut self.modules);
+        self.modules = template.modules;
+        self.modules.extend(modules);
+        for arg in &template.finish_args {
+            self.finish_args.add_arg(arg);
+        }
+        self.build_options.extend(template.build_options);
+        self.files.extend(template.files);
+
+        Ok(())
+    }
+
+    /// Generate the desktop file, the metainfo and the icon of an
+    /// application, and install them from the main module.
+    fn generate_application_files(
+        &mut self,
+        repo: &git2::Repository,
+        dest_dir: &std::path::Path,
+    ) -> Result<()> {
+        if !matches!(self.modules.last(), Some(ModuleEntry::Module(_))) {
+            self.modules.push(ModuleEntry::Module(Box::new(Module {
+                name: self.short_id.to_lowercase(),
+                buildsystem: Buildsystem::Simple,
+                ..Module::default()
+            })));
+        }
+
+        let desktop_file = desktop::generate(self, dest_dir)?;
+        repo::add_path_to_repo(repo, &desktop_file)?;
+        let metainfo_file = metainfo::generate(self, dest_dir)?;
+        repo::add_path_to_repo(repo, &metainfo_file)?;
+        let icon = match self.icon {
+            Some(ref icon) => {
+                let kind = IconKind::from_path(icon)?;
+                kind.validate(icon)?;
+                let icon_file = dest_dir.join(format!("{}.{}", &self.id, kind.extension()));
+                if icon.canonicalize().ok() != icon_file.canonicalize().ok() {
+                    std::fs::copy(icon, &icon_file)?;
+                }
+                repo::add_path_to_repo(repo, &icon_file)?;
+                Some((icon_file, kind))
+            }
+            None => None,
+        };
+
+        if let Some(ModuleEntry::Module(main_module)) = self.modules.last_mut() {
+            main_module.add_file_install(
+                &format!("{}.desktop", &self.id),
+                "${FLATPAK_DEST}/share/applications",
+            );
+            main_module.add_file_install(
+                &format!("{}.metainfo.xml", &self.id),
+                "${FLATPAK_DEST}/share/metainfo",
+            );
+            if let Some((icon_file, kind)) = icon {
+                let file_name = icon_file.file_name().unwrap_or_default().to_string_lossy();
+                main_module.add_file_install(&file_name, &kind.install_dir());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Generate the manifest files and surroundings in the `repo`.
     ///
     /// Returns the path of the generated manifest.
     pub fn generate<P: AsRef<std::path::Path>>(
-        self,
+        mut self,
         repo: &git2::Repository,
         dest: P,
     ) -> Result<()> {
@@ -145,16 +332,40 @@ impl Manifest {
         let mut manifest_file = dest_dir.clone();
         manifest_file.push(format!("{}.json", &self.id));
 
+        // Don't overwrite the project files.
+        if let Some(file) = self
+            .files
+            .iter()
+            .find(|file| dest_dir.join(&file.path).exists())
+        {
+            return Err(anyerror!(format!("{:?} already exists", file.path)));
+        }
+        for file in std::mem::take(&mut self.files) {
+            let path = dest_dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, file.content)?;
+            repo::add_path_to_repo(repo, &path)?;
+        }
+
         if self.is_extension() {
+            if let Some(ModuleEntry::Module(main_module)) = self.modules.last_mut() {
+                main_module.add_metainfo(&self.id);
+            }
+
             let metainfo_file = metainfo::generate(&self, &dest_dir)?;
 
             repo::add_path_to_repo(repo, metainfo_file)?;
 
             let flathub = flathub::Flathub {
-                skip_icons_check: true,
+                skip_icons_check: Some(true),
+                ..flathub::Flathub::default()
             };
             let flathub_file = flathub.generate(&dest_dir)?;
             repo::add_path_to_repo(repo, flathub_file)?;
+        } else if matches!(self.package_type, PackageType::Application) {
+            self.generate_application_files(repo, &dest_dir)?;
         }
 
         let need_shared_modules = self.need_shared_modules;
@@ -177,8 +388,20 @@ impl Manifest {
         Ok(())
     }
 
-    pub(crate) fn prompt_with_id(id: Option<&str>) -> Option<Manifest> {
-        let package_type = PackageType::prompt().unwrap();
+    /// Create the manifest from the `answers`, prompting for the
+    /// missing values with `ui`. `id` is the package id, if already
+    /// known. `project_dir` is where to search shared-modules.
+    ///
+    /// A missing answer the `ui` doesn't provide is an error. Lists
+    /// (SDK extensions and modules) default to being empty.
+    pub(crate) fn prompt_with_id(
+        id: Option<&str>,
+        project_dir: Option<&std::path::Path>,
+        answers: &Answers,
+        ui: &mut dyn Ui,
+    ) -> Result<Manifest> {
+        let package_type: PackageType =
+            answer_or_prompt(answers.package_type.as_deref(), ui, "package type")?;
         let use_base_app = matches!(
             package_type,
             PackageType::GimpPlugin | PackageType::LinuxAudioPlugin
@@ -191,7 +414,7 @@ impl Manifest {
                     "org.freedesktop.LinuxAudio.BaseExtension".to_string(),
                     "stable".to_string(),
                 ),
-                _ => Runtime::prompt().unwrap(),
+                _ => answer_or_prompt(answers.runtime.as_deref(), ui, "runtime")?,
             }
         };
 
@@ -205,19 +428,45 @@ impl Manifest {
                     Runtime::Gnome(ref s) => Sdk::Gnome(s.clone()),
                     Runtime::Kde(ref s) => Sdk::Kde(s.clone()),
                     Runtime::Qt6(ref s) => Sdk::Qt6(s.clone()),
-                    _ => Sdk::prompt().unwrap(),
+                    _ => answer_or_prompt(answers.sdk.as_deref(), ui, "SDK")?,
                 },
             }
         };
 
-        let sdk_extensions = {
-            let mut extensions = vec![];
+        let sdk_extensions = match answers.sdk_extensions {
+            Some(ref extensions) => extensions
+                .iter()
+                .map(|ext| {
+                    ext.parse::<SdkExtension>()
+                        .map_err(|err| anyerror!(format!("Invalid SDK extension: {err}")))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            None => {
+                let mut extensions = vec![];
+
+                while let Some(ext) = SdkExtension::prompt(ui) {
+                    extensions.push(ext);
+                }
 
-            while let Some(ext) = SdkExtension::prompt() {
-                extensions.push(ext);
+                extensions
             }
+        };
 
-            extensions
+        let finish_args = match package_type {
+            PackageType::Application => match answers.finish_profiles {
+                Some(ref profiles) => {
+                    let mut finish_args = FinishArgs::default();
+                    for profile in profiles {
+                        let profile = profile
+                            .parse()
+                            .map_err(|err| anyerror!(format!("Invalid profile: {err}")))?;
+                        finish_args.add_profile(&profile);
+                    }
+                    finish_args
+                }
+                None => FinishArgs::prompt(ui).unwrap_or_default(),
+            },
+            _ => FinishArgs::default(),
         };
 
         let base_id = package_type.base_id();
@@ -227,32 +476,87 @@ impl Manifest {
             short_id = id.rsplit_once('.').unwrap_or(("", id)).1.to_string();
             id.to_string()
         } else {
-            short_id = Input::new()
-                .with_prompt(format!("Package ID {base_id}"))
-                .interact()
-                .unwrap();
+            short_id = match answers.short_id {
+                Some(ref short_id) => short_id.clone(),
+                None => ui
+                    .input(&format!("Package ID {base_id}"))
+                    .ok_or_else(|| Error::MissingAnswer("package id".into()))?,
+            };
             format!("{base_id}{short_id}")
         };
 
-        let mut need_shared_modules = false;
-        let mut modules = vec![];
-        while let Some(module) = ModuleEntry::prompt() {
-            if matches!(module, ModuleEntry::SharedModule(_)) {
-                need_shared_modules = true;
+        let (command, icon, categories, metainfo) = match package_type {
+            PackageType::Application => {
+                let command = match answers.command {
+                    Some(ref command) => command.clone(),
+                    None => ui
+                        .input("Command")
+                        .ok_or_else(|| Error::MissingAnswer("command".into()))?,
+                };
+                let icon = match answers.icon {
+                    Some(ref icon) => Some(icon.into()),
+                    None => ui
+                        .input_optional("Icon file (SVG or PNG, empty for none)")
+                        .filter(|icon| !icon.is_empty())
+                        .map(std::path::PathBuf::from),
+                };
+                if icon.is_none() {
+                    // The desktop file refers to the icon anyway.
+                    eprintln!(
+                        "Warning: no icon. One must be installed as {id} \
+                         for the desktop file."
+                    );
+                }
+                let categories = match answers.categories {
+                    Some(ref categories) => categories.clone(),
+                    None => {
+                        let default = desktop::MAIN_CATEGORIES
+                            .iter()
+                            .position(|category| *category == desktop::DEFAULT_CATEGORY)
+                            .unwrap_or_default();
+                        ui.select("Main category", desktop::MAIN_CATEGORIES, default)
+                            .map(|idx| vec![desktop::MAIN_CATEGORIES[idx].to_string()])
+                            .unwrap_or_default()
+                    }
+                };
+                let metainfo = AppMetainfo::prompt_with_answers(answers, ui)?;
+                (Some(command), icon, categories, metainfo)
             }
-            modules.push(module);
-        }
+            _ => (None, None, vec![], AppMetainfo::default()),
+        };
+
+        let modules = match answers.modules {
+            Some(ref modules) => {
+                let buildsystem = answers
+                    .buildsystem
+                    .as_deref()
+                    .map(str::parse::<Buildsystem>)
+                    .transpose()
+                    .map_err(|err| anyerror!(format!("Invalid build system: {err}")))?
+                    .unwrap_or_default();
+                modules
+                    .iter()
+                    .map(|module| {
+                        ModuleEntry::from_answer(module, &buildsystem)
+                            .map_err(|err| anyerror!(format!("Invalid module: {err}")))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            None => {
+                let mut modules = vec![];
 
-        match package_type {
-            PackageType::GimpPlugin | PackageType::LinuxAudioPlugin => {
-                if let Some(ModuleEntry::Module(main_module)) = modules.last_mut() {
-                    main_module.add_metainfo(&id);
+                while let Some(module) = ModuleEntry::prompt_in(ui, project_dir) {
+                    modules.push(module);
                 }
+
+                modules
             }
-            _ => (),
-        }
+        };
+        let need_shared_modules = modules
+            .iter()
+            .any(|module| matches!(module, ModuleEntry::SharedModule(_)));
 
-        Some(Manifest {
+        Ok(Manifest {
             short_id,
             id,
             package_type,
@@ -261,11 +565,31 @@ impl Manifest {
             sdk,
             sdk_extensions,
             modules,
+            finish_args,
+            command,
+            icon,
+            categories,
+            metainfo,
             need_shared_modules,
+            ..Manifest::default()
         })
     }
 }
 
+/// Return the value parsed from `answer`, or prompt for it with `ui`.
+/// `what` is used for error reporting.
+fn answer_or_prompt<T>(answer: Option<&str>, ui: &mut dyn Ui, what: &str) -> Result<T>
+where
+    T: Prompt + std::str::FromStr<Err = String>,
+{
+    match answer {
+        Some(answer) => answer
+            .parse::<T>()
+            .map_err(|err| anyerror!(format!("Invalid {what}: {err}"))),
+        None => T::prompt(ui).ok_or_else(|| Error::MissingAnswer(what.into()).into()),
+    }
+}
+
 impl From<Manifest> for serde_json::Value {
     fn from(manifest: Manifest) -> serde_json::Value {
         let mut data = json!({
@@ -274,6 +598,9 @@ impl From<Manifest> for serde_json::Value {
             "runtime-version": manifest.runtime.to_string_version(),
             "sdk": manifest.sdk.to_string(),
         });
+        if let Some(ref command) = manifest.command {
+            data["command"] = json!(command);
+        }
         match manifest.package_type {
             PackageType::LinuxAudioPlugin | PackageType::GimpPlugin => {
                 data["build-extension"] = json!(true);
@@ -290,6 +617,15 @@ impl From<Manifest> for serde_json::Value {
             }
             _ => {}
         }
+        if !manifest.build_options.is_empty() {
+            let mut build_options = data
+                .get("build-options")
+                .and_then(serde_json::Value::as_object)
+                .cloned()
+                .unwrap_or_default();
+            build_options.extend(manifest.build_options);
+            data["build-options"] = build_options.into();
+        }
         let sdk_extensions: Vec<serde_json::Value> = manifest
             .sdk_extensions
             .iter()
@@ -298,6 +634,9 @@ impl From<Manifest> for serde_json::Value {
         if !sdk_extensions.is_empty() {
             data["sdk-extensions"] = sdk_extensions.into();
         }
+        if !manifest.finish_args.is_empty() {
+            data["finish-args"] = manifest.finish_args.to_args().into();
+        }
 
         data["modules"] = manifest
             .modules
@@ -308,3 +647,261 @@ impl From<Manifest> for serde_json::Value {
         data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::tui::{NonInteractiveUi, ScriptedUi};
+    use super::*;
+
+    /// Generate the manifest for `id` from the `script` in a new
+    /// repository in `dir`. Return the paths staged in the index.
+    fn generate(dir: &Path, id: &str, script: &[&str]) -> Vec<String> {
+        let repo = git2::Repository::init(dir).unwrap();
+        let mut ui = ScriptedUi::new(script);
+        let manifest =
+            Manifest::prompt_with_id(Some(id), None, &Answers::default(), &mut ui).unwrap();
+        assert!(ui.is_exhausted());
+        manifest.generate(&repo, dir).unwrap();
+
+        let index = repo.index().unwrap();
+        let mut staged: Vec<String> = index
+            .iter()
+            .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+            .collect();
+        staged.sort();
+        staged
+    }
+
+    fn assert_golden(dir: &Path, file: &str, golden: &str) {
+        let content = std::fs::read_to_string(dir.join(file)).unwrap();
+        assert_eq!(content, golden, "{file} doesn't match the golden file");
+    }
+
+    #[test]
+    fn test_generate_application() {
+        let dir = tempfile::tempdir().unwrap();
+        let staged = generate(
+            dir.path(),
+            "org.example.App",
+            &[
+                "Application",
+                "GNOME",
+                "Rust (stable)",
+                "None",
+                "yes",
+                "yes",
+                "no",
+                "app",
+                "",
+                "Development",
+                "An example application",
+                "An application to show the generated files.",
+                "Example Developers",
+                "GPL-3.0-or-later",
+                "https://example.org",
+                "yes",
+                "https://example.org/screenshot.png",
+                "no",
+                "",
+                "fltk",
+                "Custom",
+                "app",
+                "meson",
+                "-Dtests=false",
+                "",
+                "None",
+            ],
+        );
+        assert_eq!(
+            staged,
+            [
+                "org.example.App.desktop",
+                "org.example.App.json",
+                "org.example.App.metainfo.xml",
+            ]
+        );
+        assert_golden(
+            dir.path(),
+            "org.example.App.json",
+            include_str!("testdata/application.json"),
+        );
+        assert_golden(
+            dir.path(),
+            "org.example.App.desktop",
+            include_str!("testdata/application.desktop"),
+        );
+        assert_golden(
+            dir.path(),
+            "org.example.App.metainfo.xml",
+            include_str!("testdata/application.metainfo.xml"),
+        );
+
+        // Only the release is left to `release add`.
+        let xml = std::fs::read_to_string(dir.path().join("org.example.App.metainfo.xml")).unwrap();
+        let context = metainfo::check::CheckContext {
+            manifest_id: Some("org.example.App"),
+            project_dir: dir.path(),
+        };
+        let diagnostics: Vec<_> = metainfo::check::check(&xml, &context)
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(diagnostics, ["no <release>"]);
+    }
+
+    #[test]
+    fn test_generate_linux_audio_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = "org.freedesktop.LinuxAudio.Plugins.Foo";
+        let staged = generate(
+            dir.path(),
+            id,
+            &[
+                "Linux Audio plugin",
+                "None",
+                "Custom",
+                "foo",
+                "cmake",
+                "",
+                "yes",
+                "None",
+            ],
+        );
+        assert_eq!(
+            staged,
+            [
+                "flathub.json",
+                "org.freedesktop.LinuxAudio.Plugins.Foo.json",
+                "org.freedesktop.LinuxAudio.Plugins.Foo.metainfo.xml",
+            ]
+        );
+        assert_golden(
+            dir.path(),
+            &format!("{id}.json"),
+            include_str!("testdata/linux-audio-plugin.json"),
+        );
+        assert_golden(
+            dir.path(),
+            &format!("{id}.metainfo.xml"),
+            include_str!("testdata/linux-audio-plugin.metainfo.xml"),
+        );
+        assert_golden(
+            dir.path(),
+            "flathub.json",
+            include_str!("testdata/plugin-flathub.json"),
+        );
+    }
+
+    #[test]
+    fn test_generate_gimp_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = "org.gimp.GIMP.Plugin.Bar";
+        let staged = generate(
+            dir.path(),
+            id,
+            &[
+                "GIMP Plugin",
+                "None",
+                "Custom",
+                "bar",
+                "autotools",
+                "",
+                "no",
+                "None",
+            ],
+        );
+        assert_eq!(
+            staged,
+            [
+                "flathub.json",
+                "org.gimp.GIMP.Plugin.Bar.json",
+                "org.gimp.GIMP.Plugin.Bar.metainfo.xml",
+            ]
+        );
+        assert_golden(
+            dir.path(),
+            &format!("{id}.json"),
+            include_str!("testdata/gimp-plugin.json"),
+        );
+        assert_golden(
+            dir.path(),
+            &format!("{id}.metainfo.xml"),
+            include_str!("testdata/gimp-plugin.metainfo.xml"),
+        );
+        assert_golden(
+            dir.path(),
+            "flathub.json",
+            include_str!("testdata/plugin-flathub.json"),
+        );
+    }
+
+    #[test]
+    fn test_non_interactive() {
+        let answers = Answers {
+            package_type: Some("application".to_string()),
+            ..Answers::default()
+        };
+        let err = Manifest::prompt_with_id(
+            Some("org.example.App"),
+            None,
+            &answers,
+            &mut NonInteractiveUi,
+        )
+        .unwrap_err();
+        assert!(matches!(err.source(), Error::MissingAnswer(what) if what == "runtime"));
+
+        let answers = Answers {
+            package_type: Some("application".to_string()),
+            runtime: Some("kde".to_string()),
+            command: Some("app".to_string()),
+            modules: Some(vec!["app:cmake-ninja".to_string()]),
+            ..Answers::default()
+        };
+        let manifest = Manifest::prompt_with_id(
+            Some("org.example.App"),
+            None,
+            &answers,
+            &mut NonInteractiveUi,
+        )
+        .unwrap();
+        assert!(matches!(manifest.sdk, Sdk::Kde(_)));
+        assert!(manifest.sdk_extensions.is_empty());
+        assert_eq!(manifest.modules.len(), 1);
+    }
+
+    #[test]
+    fn test_template_no_overwrite() {
+        let answers = Answers {
+            package_type: Some("application".to_string()),
+            runtime: Some("kde".to_string()),
+            command: Some("app".to_string()),
+            modules: Some(vec!["app:cmake-ninja".to_string()]),
+            ..Answers::default()
+        };
+        let mut manifest = Manifest::prompt_with_id(
+            Some("org.example.App"),
+            None,
+            &answers,
+            &mut NonInteractiveUi,
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("README.md"), "Mine").unwrap();
+        manifest.files = ["NOTES.md", "README.md"]
+            .iter()
+            .map(|path| template::TemplateFile {
+                path: path.to_string(),
+                content: "Template".to_string(),
+            })
+            .collect();
+        assert!(manifest.generate(&repo, dir.path()).is_err());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("README.md")).unwrap(),
+            "Mine"
+        );
+        assert!(!dir.path().join("NOTES.md").exists());
+    }
+}
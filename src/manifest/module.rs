@@ -1,32 +1,67 @@
-// SPDX-FileCopyrightText: 2020-2025 Hubert Figuière
+// SPDX-FileCopyrightText: 2020-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-#[derive(Clone, Debug)]
+use super::config;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
 pub(crate) enum ModuleEntry {
     SharedModule(String),
-    Module(Module),
+    Module(Box<Module>),
+}
+
+impl ModuleEntry {
+    /// Create a module entry from an answer. `answer` is either a
+    /// known module, a shared-modules path, or `NAME[:BUILDSYSTEM]`
+    /// for a custom module. `buildsystem` is used if none is specified.
+    pub fn from_answer(answer: &str, buildsystem: &Buildsystem) -> Result<ModuleEntry, String> {
+        if let Some(entry) = config::DEFAULT_MODULES.get(answer) {
+            return Ok(entry.clone());
+        }
+        if answer.starts_with(&format!("{}/", crate::flathub::SHARED_MODULES)) {
+            return Ok(ModuleEntry::SharedModule(answer.to_string()));
+        }
+        let (name, buildsystem) = match answer.split_once(':') {
+            Some((name, buildsystem)) => (name, buildsystem.parse()?),
+            None => (answer, buildsystem.clone()),
+        };
+        if name.is_empty() {
+            return Err("empty module name".to_string());
+        }
+
+        Ok(ModuleEntry::Module(Box::new(Module {
+            name: name.to_string(),
+            buildsystem,
+            ..Module::default()
+        })))
+    }
 }
 
 impl From<&ModuleEntry> for serde_json::Value {
     fn from(entry: &ModuleEntry) -> serde_json::Value {
-        match entry {
-            ModuleEntry::SharedModule(s) => json!(s),
-            ModuleEntry::Module(m) => json!(m),
-        }
+        json!(entry)
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub(crate) enum SourceType {
+    #[default]
     Archive,
     Git,
-    Patch,
+    Bzr,
+    Svn,
+    Dir,
     File,
+    Script,
+    Shell,
+    Patch,
+    ExtraData,
+    Inline,
 }
 
 impl std::fmt::Display for SourceType {
@@ -37,23 +72,157 @@ impl std::fmt::Display for SourceType {
             match *self {
                 SourceType::Archive => "archive",
                 SourceType::Git => "git",
-                SourceType::Patch => "patch",
+                SourceType::Bzr => "bzr",
+                SourceType::Svn => "svn",
+                SourceType::Dir => "dir",
                 SourceType::File => "file",
+                SourceType::Script => "script",
+                SourceType::Shell => "shell",
+                SourceType::Patch => "patch",
+                SourceType::ExtraData => "extra-data",
+                SourceType::Inline => "inline",
             }
         )
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+/// A module source, as in the flatpak-builder manifest. Which fields
+/// apply depend on the type. Other keys are preserved.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
 pub(crate) struct Source {
     #[serde(rename = "type")]
     pub(crate) type_: SourceType,
-    pub path: String,
+    /// The remote location, for archive, file, git, bzr, svn and
+    /// extra-data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The local path, relative to the manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The local paths, for patch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirror_urls: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dest_filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// The revision, for bzr and svn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_components: Option<u32>,
+    /// The commands, for script and shell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commands: Option<Vec<String>>,
+    /// The content, for inline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<String>,
+    /// The file name, for extra-data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// The size, for extra-data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only_arches: Option<Vec<String>>,
+    /// The other keys.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Default, Serialize)]
+impl Source {
+    /// A file source with the local `path`.
+    pub fn file(path: &str) -> Source {
+        Source {
+            type_: SourceType::File,
+            path: Some(path.to_string()),
+            ..Source::default()
+        }
+    }
+
+    /// An archive source at `url`.
+    pub fn archive(url: &str, sha256: &str) -> Source {
+        Source {
+            type_: SourceType::Archive,
+            url: Some(url.to_string()),
+            sha256: Some(sha256.to_string()),
+            ..Source::default()
+        }
+    }
+
+    /// Check that the fields required by the type are there.
+    pub fn validate(&self) -> Result<(), String> {
+        let has_checksum = [&self.md5, &self.sha1, &self.sha256, &self.sha512]
+            .iter()
+            .any(|checksum| checksum.is_some());
+        match self.type_ {
+            SourceType::Archive | SourceType::File => {
+                match (&self.url, &self.path) {
+                    (Some(_), Some(_)) => return Err("both url and path".to_string()),
+                    (None, None) => return Err("missing url or path".to_string()),
+                    (Some(_), None) if !has_checksum => {
+                        return Err("missing checksum for url".to_string())
+                    }
+                    _ => {}
+                }
+                if self.type_ == SourceType::File && self.strip_components.is_some() {
+                    return Err("strip-components only applies to archive".to_string());
+                }
+            }
+            SourceType::Git => {
+                if self.url.is_none() && self.path.is_none() {
+                    return Err("missing url or path".to_string());
+                }
+                if self.branch.is_some() && self.tag.is_some() {
+                    return Err("branch with tag".to_string());
+                }
+            }
+            SourceType::Bzr | SourceType::Svn if self.url.is_none() => {
+                return Err("missing url".to_string())
+            }
+            SourceType::Dir if self.path.is_none() => return Err("missing path".to_string()),
+            SourceType::Script | SourceType::Shell if self.commands.is_none() => {
+                return Err("missing commands".to_string())
+            }
+            SourceType::Patch if self.path.is_none() && self.paths.is_none() => {
+                return Err("missing path or paths".to_string())
+            }
+            SourceType::ExtraData
+                if self.filename.is_none()
+                    || self.url.is_none()
+                    || self.sha256.is_none()
+                    || self.size.is_none() =>
+            {
+                return Err("extra-data requires filename, url, sha256 and size".to_string());
+            }
+            SourceType::Inline if self.contents.is_none() => {
+                return Err("missing contents".to_string())
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum Buildsystem {
     #[default]
@@ -65,21 +234,80 @@ pub(crate) enum Buildsystem {
     Qmake,
 }
 
-#[derive(Clone, Debug, Default, Serialize)]
-#[serde(rename_all = "kebab-case")]
+impl std::str::FromStr for Buildsystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "autotools" => Ok(Self::Autotools),
+            "cmake" => Ok(Self::Cmake),
+            "cmake-ninja" => Ok(Self::CmakeNinja),
+            "simple" => Ok(Self::Simple),
+            "meson" => Ok(Self::Meson),
+            "qmake" => Ok(Self::Qmake),
+            _ => Err(format!("unknown build system '{s}'")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
 pub(crate) struct Module {
     pub name: String,
+    /// Whether the module is disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
     pub(crate) buildsystem: Buildsystem,
+    /// Whether to build in a separate directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builddir: Option<bool>,
+    /// The subdirectory of the sources to build.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    /// Don't run autogen for autotools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_autogen: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only_arches: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_options: Option<serde_json::Map<String, serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config_opts: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub make_args: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub make_install_args: Option<Vec<String>>,
+    /// The build commands, for the simple build system.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_commands: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cleanup: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub cleanup_commands: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub post_install: Option<Vec<String>>,
     pub(crate) sources: Vec<Source>,
+    /// The nested modules.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) modules: Vec<ModuleEntry>,
+    /// The data for flatpak-external-data-checker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x_checker_data: Option<serde_json::Value>,
+    /// The other keys.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Module {
+    /// Add the `file` to the module sources and install it in
+    /// `dest_dir`.
+    pub fn add_file_install(&mut self, file: &str, dest_dir: &str) {
+        self.post_install
+            .get_or_insert_with(Vec::new)
+            .push(format!("install -Dm644 {file} -t {dest_dir}"));
+        self.sources.push(Source::file(file));
+    }
+
     /// Add the metainfo to the module
     pub fn add_metainfo(&mut self, id: &str) {
         let metainfo = format!("{id}.metainfo.xml");
@@ -90,11 +318,7 @@ impl Module {
             ),
             "appstreamcli compose --components=${FLATPAK_ID} --prefix=/ --origin=${FLATPAK_ID} --result-root=${FLATPAK_DEST} --data-dir=${FLATPAK_DEST}/share/app-info/xmls ${FLATPAK_DEST}".to_string()
         ]);
-        self.sources.push(Source {
-            type_: SourceType::File,
-            path: metainfo,
-            sha256: None,
-        });
+        self.sources.push(Source::file(&metainfo));
     }
 }
 
@@ -118,4 +342,97 @@ mod tests {
         test_value(Buildsystem::Meson, "meson");
         test_value(Buildsystem::Qmake, "qmake");
     }
+
+    #[test]
+    fn test_module_from_answer() {
+        let entry = ModuleEntry::from_answer("fltk", &Buildsystem::Meson).unwrap();
+        assert!(matches!(entry, ModuleEntry::Module(m) if m.name == "fltk-static"));
+
+        let entry =
+            ModuleEntry::from_answer("shared-modules/lua5.4/lua-5.4.json", &Buildsystem::Meson)
+                .unwrap();
+        assert!(matches!(entry, ModuleEntry::SharedModule(_)));
+
+        let entry = ModuleEntry::from_answer("foo", &Buildsystem::Meson).unwrap();
+        assert!(
+            matches!(entry, ModuleEntry::Module(m) if m.name == "foo" && matches!(m.buildsystem, Buildsystem::Meson))
+        );
+
+        let entry = ModuleEntry::from_answer("foo:cmake-ninja", &Buildsystem::Meson).unwrap();
+        assert!(
+            matches!(entry, ModuleEntry::Module(m) if matches!(m.buildsystem, Buildsystem::CmakeNinja))
+        );
+
+        assert!(ModuleEntry::from_answer("foo:scons", &Buildsystem::Meson).is_err());
+    }
+
+    #[test]
+    fn test_module_round_trip() {
+        let value = json!({
+            "name": "foo",
+            "buildsystem": "simple",
+            "subdir": "src",
+            "build-options": { "env": { "FOO": "1" } },
+            "build-commands": [ "make install PREFIX=${FLATPAK_DEST}" ],
+            "sources": [ { "type": "dir", "path": "." } ],
+            "modules": [
+                "shared-modules/lua5.4/lua-5.4.json",
+                { "name": "bar", "buildsystem": "meson", "sources": [] }
+            ],
+            "x-checker-data": { "type": "anitya" },
+            "foo-bar": true
+        });
+        let entry: ModuleEntry = serde_json::from_value(value.clone()).unwrap();
+        let ModuleEntry::Module(ref module) = entry else {
+            panic!("not a module");
+        };
+        assert_eq!(module.modules.len(), 2);
+        assert_eq!(serde_json::Value::from(&entry), value);
+    }
+
+    #[test]
+    fn test_sources() {
+        let source: Source = serde_json::from_value(json!({
+            "type": "git",
+            "url": "https://example.org/foo.git",
+            "tag": "v1.0",
+            "commit": "0123456789abcdef",
+            "x-checker-data": { "type": "git" }
+        }))
+        .unwrap();
+        assert_eq!(source.type_, SourceType::Git);
+        assert!(source.validate().is_ok());
+        assert_eq!(
+            serde_json::to_string(&source).unwrap(),
+            r#"{"type":"git","url":"https://example.org/foo.git","tag":"v1.0","commit":"0123456789abcdef","x-checker-data":{"type":"git"}}"#
+        );
+
+        let source = Source::archive("https://example.org/foo.tar.xz", "abcd");
+        assert_eq!(
+            json!(source),
+            json!({ "type": "archive", "url": "https://example.org/foo.tar.xz", "sha256": "abcd" })
+        );
+        assert_eq!(json!(SourceType::ExtraData), json!("extra-data"));
+
+        let source = Source {
+            type_: SourceType::Archive,
+            url: Some("https://example.org/foo.tar.xz".to_string()),
+            ..Source::default()
+        };
+        assert!(source.validate().is_err());
+        let source: Source = serde_json::from_value(json!({
+            "type": "file",
+            "url": "https://example.org/foo.txt",
+            "sha1": "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"
+        }))
+        .unwrap();
+        assert!(source.validate().is_ok());
+        assert!(Source::file("foo.desktop").validate().is_ok());
+        assert!(Source {
+            type_: SourceType::Shell,
+            ..Source::default()
+        }
+        .validate()
+        .is_err());
+    }
 }
@@ -0,0 +1,205 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Pin the git sources to the commit of their tag or branch.
+
+use serde_json::Value as JsonValue;
+
+use crate::{repo, Result};
+
+/// The references of a remote, as listed by `repo::ls_remote`.
+pub(crate) type Heads = Vec<(String, git2::Oid)>;
+
+/// The status of a git source.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Status {
+    /// The commit was added.
+    Pinned(git2::Oid),
+    /// The commit is missing. Not added when checking.
+    Unpinned(git2::Oid),
+    /// The commit matches the reference.
+    Verified,
+    /// The reference is at another commit.
+    Mismatch(git2::Oid),
+    /// The reference isn't in the remote.
+    NotFound,
+}
+
+impl Status {
+    /// Whether the status is an error.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Status::Unpinned(_) | Status::Mismatch(_) | Status::NotFound
+        )
+    }
+}
+
+/// The status of a git source with its URL and reference.
+#[derive(Debug)]
+pub(crate) struct Report {
+    pub url: String,
+    /// The reference, `tag NAME` or `branch NAME`.
+    pub reference: String,
+    pub status: Status,
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: ", self.url, self.reference)?;
+        match self.status {
+            Status::Pinned(commit) => write!(f, "pinned to {commit}"),
+            Status::Unpinned(commit) => write!(f, "not pinned, at {commit}"),
+            Status::Verified => write!(f, "verified"),
+            Status::Mismatch(commit) => write!(f, "pinned commit mismatch, at {commit}"),
+            Status::NotFound => write!(f, "not found in the remote"),
+        }
+    }
+}
+
+/// Pin the git `source`. Return `None` if it has no URL, or no tag or
+/// branch.
+fn pin_source(
+    source: &mut serde_json::Map<String, JsonValue>,
+    ls_remote: &mut dyn FnMut(&str) -> Result<Heads>,
+    check: bool,
+) -> Result<Option<Report>> {
+    if source.get("type").and_then(JsonValue::as_str) != Some("git") {
+        return Ok(None);
+    }
+    let Some(url) = source.get("url").and_then(JsonValue::as_str) else {
+        return Ok(None);
+    };
+    let Some((key, name)) = ["tag", "branch"].iter().find_map(|key| {
+        source
+            .get(*key)
+            .and_then(JsonValue::as_str)
+            .map(|name| (*key, name))
+    }) else {
+        return Ok(None);
+    };
+    let heads = ls_remote(url)?;
+    let remote = match key {
+        "tag" => repo::tag_commit(&heads, name),
+        _ => repo::branch_commit(&heads, name),
+    };
+    let mut report = Report {
+        url: url.to_string(),
+        reference: format!("{key} {name}"),
+        status: Status::NotFound,
+    };
+    let Some(remote) = remote else {
+        return Ok(Some(report));
+    };
+
+    report.status = match source.get("commit").and_then(JsonValue::as_str) {
+        Some(commit) if !commit.is_empty() && remote.to_string().starts_with(commit) => {
+            Status::Verified
+        }
+        Some(_) => Status::Mismatch(remote),
+        None if check => Status::Unpinned(remote),
+        None => {
+            let idx = source
+                .keys()
+                .position(|k| k == key)
+                .map_or(source.len(), |idx| idx + 1);
+            source.shift_insert(idx, "commit".to_string(), remote.to_string().into());
+            Status::Pinned(remote)
+        }
+    };
+
+    Ok(Some(report))
+}
+
+/// Pin the git sources in `value`, a manifest, a module or an array of
+/// modules, recursively. Included files are skipped. `ls_remote` lists
+/// the references of the remote at an URL. Nothing is changed when
+/// `check`.
+pub(crate) fn pin_sources(
+    value: &mut JsonValue,
+    ls_remote: &mut dyn FnMut(&str) -> Result<Heads>,
+    check: bool,
+) -> Result<Vec<Report>> {
+    let mut reports = vec![];
+    match value {
+        JsonValue::Array(modules) => {
+            for module in modules {
+                reports.extend(pin_sources(module, ls_remote, check)?);
+            }
+        }
+        JsonValue::Object(module) => {
+            if let Some(sources) = module.get_mut("sources").and_then(JsonValue::as_array_mut) {
+                for source in sources.iter_mut().filter_map(JsonValue::as_object_mut) {
+                    reports.extend(pin_source(source, ls_remote, check)?);
+                }
+            }
+            if let Some(modules) = module.get_mut("modules") {
+                reports.extend(pin_sources(modules, ls_remote, check)?);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_pin_sources() {
+        let v1 = git2::Oid::from_str("1111111111111111111111111111111111111111").unwrap();
+        let v2 = git2::Oid::from_str("2222222222222222222222222222222222222222").unwrap();
+        let mut ls_remote = |_: &str| -> Result<Heads> {
+            Ok(vec![
+                ("refs/heads/main".to_string(), v2),
+                ("refs/tags/v1.0".to_string(), v1),
+                ("refs/tags/v2.0".to_string(), v2),
+            ])
+        };
+        let mut manifest = json!({
+            "modules": [{
+                "name": "app",
+                "sources": [
+                    { "type": "git", "url": "https://example.org/app.git", "tag": "v1.0", "x-checker-data": {} },
+                    { "type": "git", "url": "https://example.org/app.git", "tag": "v2.0", "commit": "1111111" },
+                    { "type": "git", "url": "https://example.org/app.git", "tag": "v3.0" },
+                    { "type": "archive", "url": "https://example.org/app.tar.gz" }
+                ],
+                "modules": [{
+                    "name": "dep",
+                    "sources": [{ "type": "git", "url": "https://example.org/dep.git", "branch": "main", "commit": "2222222222222222222222222222222222222222" }]
+                }]
+            }]
+        });
+
+        let check = pin_sources(&mut manifest.clone(), &mut ls_remote, true).unwrap();
+        assert_eq!(check[0].status, Status::Unpinned(v1));
+
+        let reports = pin_sources(&mut manifest, &mut ls_remote, false).unwrap();
+        let statuses: Vec<_> = reports.iter().map(|report| &report.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                &Status::Pinned(v1),
+                &Status::Mismatch(v2),
+                &Status::NotFound,
+                &Status::Verified
+            ]
+        );
+        assert_eq!(
+            manifest["modules"][0]["sources"][0],
+            json!({ "type": "git", "url": "https://example.org/app.git", "tag": "v1.0", "commit": v1.to_string(), "x-checker-data": {} })
+        );
+        assert_eq!(
+            serde_json::to_string(&manifest["modules"][0]["sources"][0]).unwrap(),
+            format!(
+                r#"{{"type":"git","url":"https://example.org/app.git","tag":"v1.0","commit":"{v1}","x-checker-data":{{}}}}"#
+            )
+        );
+    }
+}
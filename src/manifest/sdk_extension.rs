@@ -1,4 +1,4 @@
-// SPDX-FileCopyrightText: 2020-2025 Hubert Figuière
+// SPDX-FileCopyrightText: 2020-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
@@ -35,3 +35,33 @@ impl std::fmt::Display for SdkExtension {
         }
     }
 }
+
+impl std::str::FromStr for SdkExtension {
+    type Err = String;
+
+    /// Parse `NAME[//VERSION]`. The version is required for the
+    /// versioned extensions.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, version) = super::split_version(s);
+        let versioned = |f: fn(String) -> SdkExtension| {
+            version
+                .clone()
+                .map(f)
+                .ok_or_else(|| format!("SDK extension '{name}' requires a version"))
+        };
+        match name {
+            "rust" | "rust-stable" => Ok(Self::Rust),
+            "rust-nightly" => Ok(Self::RustNightly),
+            "java" | "openjdk" => versioned(Self::Java),
+            "node" => versioned(Self::Node),
+            "php" => versioned(Self::Php),
+            "go" | "golang" => Ok(Self::GoLang),
+            "texlive" => Ok(Self::TexLive),
+            "llvm" => versioned(Self::Llvm),
+            "dotnet" => versioned(Self::DotNet),
+            "mono" => versioned(Self::Mono),
+            "gcc" => versioned(Self::Gcc),
+            _ => Err(format!("unknown SDK extension '{s}'")),
+        }
+    }
+}
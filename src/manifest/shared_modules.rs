@@ -0,0 +1,426 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The catalogue of shared-modules, from the submodule checkout or a
+//! cached copy.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::file as manifest_file;
+use super::tree;
+use crate::flathub::{SHARED_MODULES, SHARED_MODULES_REPO};
+use crate::{anyerror, Result};
+
+/// A module file of shared-modules.
+#[derive(Debug, Serialize)]
+pub(crate) struct SharedModule {
+    /// The path to include from the manifest.
+    pub path: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl std::fmt::Display for SharedModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.version {
+            Some(ref version) => write!(f, "{} {version} ({})", self.name, self.path),
+            None => write!(f, "{} ({})", self.name, self.path),
+        }
+    }
+}
+
+/// How far the shared-modules checkout is behind its remote.
+#[derive(Debug)]
+pub(crate) struct Drift {
+    /// The number of commits behind.
+    pub behind: usize,
+    /// The changed files in the directories of the included modules.
+    pub changed: Vec<String>,
+}
+
+/// Return the remote head of `branch`, or of the default branch, in
+/// `repo`.
+pub(crate) fn remote_head(repo: &git2::Repository, branch: Option<&str>) -> Result<git2::Oid> {
+    let names = match branch {
+        Some(branch) => vec![format!("refs/remotes/origin/{branch}")],
+        None => ["HEAD", "master", "main"]
+            .iter()
+            .map(|branch| format!("refs/remotes/origin/{branch}"))
+            .collect(),
+    };
+
+    names
+        .iter()
+        .find_map(|name| repo.find_reference(name).ok()?.resolve().ok()?.target())
+        .ok_or_else(|| anyerror!("No remote branch in the shared-modules checkout"))
+}
+
+/// Fetch `origin` in `repo`, and return the remote head like
+/// `remote_head`.
+pub(crate) fn fetch_remote_head(
+    repo: &git2::Repository,
+    branch: Option<&str>,
+) -> Result<git2::Oid> {
+    repo.find_remote("origin")?
+        .fetch(&[] as &[&str], None, None)?;
+
+    remote_head(repo, branch)
+}
+
+/// Compute the drift in `repo` from `pinned` to `remote`. `included`
+/// are the included module files, relative to the checkout.
+pub(crate) fn drift(
+    repo: &git2::Repository,
+    pinned: git2::Oid,
+    remote: git2::Oid,
+    included: &[PathBuf],
+) -> Result<Drift> {
+    let (_, behind) = repo.graph_ahead_behind(pinned, remote)?;
+    let old_tree = repo.find_commit(pinned)?.tree()?;
+    let new_tree = repo.find_commit(remote)?.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+    let changed = diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .filter(|path| included.iter().any(|file| file.parent() == path.parent()))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+
+    Ok(Drift { behind, changed })
+}
+
+/// Load the JSON file `path` in `checkout`, as an array.
+fn load_array(checkout: &Path, path: &Path) -> Result<Vec<JsonValue>> {
+    Ok(match manifest_file::load(&checkout.join(path))? {
+        JsonValue::Array(values) => values,
+        value => vec![value],
+    })
+}
+
+/// Add the local files of the `sources` of the file in `dir` to
+/// `files`, relative to `checkout`.
+fn collect_sources(
+    sources: &[JsonValue],
+    dir: &Path,
+    checkout: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for source in sources {
+        let mut paths: Vec<&str> = vec![];
+        if let JsonValue::String(include) = source {
+            let path = PathBuf::from(manifest_file::rebase_path(include, dir, Path::new("")));
+            let sources = load_array(checkout, &path)?;
+            collect_sources(&sources, path.parent().unwrap_or(dir), checkout, files)?;
+            paths.push(include);
+        }
+        paths.extend(source.get("path").and_then(JsonValue::as_str));
+        paths.extend(
+            source
+                .get("paths")
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(JsonValue::as_str),
+        );
+        for path in paths
+            .into_iter()
+            .filter(|path| !Path::new(path).is_absolute())
+        {
+            files.push(PathBuf::from(manifest_file::rebase_path(
+                path,
+                dir,
+                Path::new(""),
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Add the local files of the `module` in the file in `dir` to
+/// `files`, relative to `checkout`.
+fn collect_module(
+    module: &JsonValue,
+    dir: &Path,
+    checkout: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    if let Some(sources) = module.get("sources").and_then(JsonValue::as_array) {
+        collect_sources(sources, dir, checkout, files)?;
+    }
+    for module in manifest_file::modules(module) {
+        match module {
+            JsonValue::String(include) => {
+                let path = PathBuf::from(manifest_file::rebase_path(include, dir, Path::new("")));
+                for module in load_array(checkout, &path)? {
+                    collect_module(&module, path.parent().unwrap_or(dir), checkout, files)?;
+                }
+                files.push(path);
+            }
+            module => collect_module(module, dir, checkout, files)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the files of the module file `path` in `checkout`: the file,
+/// the included files and the local sources, like patches. They are
+/// relative to `checkout`.
+pub(crate) fn module_files(checkout: &Path, path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![path.to_path_buf()];
+    for module in load_array(checkout, path)? {
+        collect_module(
+            &module,
+            path.parent().unwrap_or(Path::new("")),
+            checkout,
+            &mut files,
+        )?;
+    }
+    if let Some(file) = files.iter().find(|file| file.starts_with("..")) {
+        return Err(anyerror!(format!(
+            "{file:?} is outside of {SHARED_MODULES}"
+        )));
+    }
+    let mut unique = vec![];
+    for file in files {
+        if !unique.contains(&file) {
+            unique.push(file);
+        }
+    }
+
+    Ok(unique)
+}
+
+/// Return the cached copy of shared-modules.
+fn cache_checkout() -> Option<PathBuf> {
+    crate::xdg::cache_dir().map(|dir| dir.join(SHARED_MODULES))
+}
+
+/// Clone or update the cached copy of shared-modules and return it.
+pub(crate) fn update_cache() -> Result<PathBuf> {
+    let dir = cache_checkout().ok_or_else(|| anyerror!("No cache directory"))?;
+    if !dir.join(".git").exists() {
+        if let Some(parent) = dir.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        git2::Repository::clone(SHARED_MODULES_REPO, &dir)?;
+        return Ok(dir);
+    }
+
+    let repo = git2::Repository::open(&dir)?;
+    let head = repo.find_commit(fetch_remote_head(&repo, None)?)?;
+    repo.reset(head.as_object(), git2::ResetType::Hard, None)?;
+
+    Ok(dir)
+}
+
+/// Return the checkout of shared-modules: the submodule of the
+/// project in `project_dir` if it is checked out, or the cached copy.
+pub(crate) fn checkout(project_dir: Option<&Path>) -> Option<PathBuf> {
+    project_dir
+        .map(|dir| dir.join(SHARED_MODULES))
+        // The submodule is an empty directory until checked out.
+        .filter(|dir| {
+            dir.read_dir()
+                .is_ok_and(|mut entries| entries.next().is_some())
+        })
+        .or_else(|| cache_checkout().filter(|dir| dir.is_dir()))
+}
+
+/// Add the modules of the file at `path`, included as `include`.
+fn index_file(path: &Path, include: &str, modules: &mut Vec<SharedModule>) {
+    let Some(value) = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<JsonValue>(&content).ok())
+    else {
+        return;
+    };
+    let values = match value {
+        JsonValue::Array(values) => values,
+        value => vec![value],
+    };
+    // Files of sources aren't modules.
+    for module in values
+        .iter()
+        .filter(|value| value.get("name").is_some() && value.get("sources").is_some())
+    {
+        modules.push(SharedModule {
+            path: include.to_string(),
+            name: module["name"].as_str().unwrap_or_default().to_string(),
+            version: module
+                .get("sources")
+                .and_then(JsonValue::as_array)
+                .and_then(|sources| {
+                    sources
+                        .iter()
+                        .find_map(|source| tree::source_summary(source).version)
+                }),
+        });
+    }
+}
+
+/// Add the modules of the files in `dir`, recursively.
+fn index_dir(dir: &Path, include_dir: &str, modules: &mut Vec<SharedModule>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let include = format!("{include_dir}/{file_name}");
+        let path = entry.path();
+        if path.is_dir() {
+            index_dir(&path, &include, modules);
+        } else if file_name.ends_with(".json") {
+            index_file(&path, &include, modules);
+        }
+    }
+}
+
+/// Index the modules of the shared-modules `checkout`, sorted by path.
+pub(crate) fn index(checkout: &Path) -> Vec<SharedModule> {
+    let mut modules = vec![];
+    index_dir(checkout, SHARED_MODULES, &mut modules);
+    modules.sort_by(|a, b| a.path.cmp(&b.path));
+
+    modules
+}
+
+/// Return the catalogue of shared-modules for the project in
+/// `project_dir`. Empty if there is no checkout.
+pub(crate) fn catalogue(project_dir: Option<&Path>) -> Vec<SharedModule> {
+    checkout(project_dir)
+        .map(|dir| index(&dir))
+        .unwrap_or_default()
+}
+
+/// Search the `modules` with `keyword` in the name or the path, case
+/// insensitive.
+pub(crate) fn search<'a>(modules: &'a [SharedModule], keyword: &str) -> Vec<&'a SharedModule> {
+    let keyword = keyword.to_lowercase();
+    modules
+        .iter()
+        .filter(|module| {
+            module.name.to_lowercase().contains(&keyword)
+                || module.path.to_lowercase().contains(&keyword)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("lua5.4")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        std::fs::write(
+            dir.path().join("lua5.4/lua-5.4.json"),
+            r#"{"name": "lua-5.4", "sources": [{"type": "archive", "url": "https://www.lua.org/ftp/lua-5.4.6.tar.gz"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("lua5.4/lua-sources.json"),
+            r#"[{"type": "patch", "path": "lua.patch"}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("glu.json"),
+            r#"{"name": "glu", "sources": []}"#,
+        )
+        .unwrap();
+
+        let modules = index(dir.path());
+        assert_eq!(modules.len(), 2);
+        assert_eq!(modules[0].path, "shared-modules/glu.json");
+        assert_eq!(
+            modules[1].to_string(),
+            "lua-5.4 5.4.6 (shared-modules/lua5.4/lua-5.4.json)"
+        );
+        assert_eq!(search(&modules, "LUA").len(), 1);
+        assert!(search(&modules, "gtk").is_empty());
+    }
+
+    #[test]
+    fn test_module_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("lua5.4")).unwrap();
+        std::fs::write(
+            dir.path().join("lua5.4/lua-5.4.json"),
+            r#"{"name": "lua-5.4", "sources": [
+                {"type": "archive", "url": "https://www.lua.org/ftp/lua-5.4.6.tar.gz"},
+                {"type": "patch", "paths": ["lua-5.4.patch", "../common/so.patch"]},
+                "lua-sources.json"
+            ], "modules": ["readline.json"]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("lua5.4/lua-sources.json"),
+            r#"[{"type": "file", "path": "lua.pc"}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("lua5.4/readline.json"),
+            r#"{"name": "readline", "sources": [{"type": "file", "path": "/etc/inputrc"}]}"#,
+        )
+        .unwrap();
+
+        let files = module_files(dir.path(), Path::new("lua5.4/lua-5.4.json")).unwrap();
+        assert_eq!(
+            files,
+            [
+                "lua5.4/lua-5.4.json",
+                "lua5.4/lua-5.4.patch",
+                "common/so.patch",
+                "lua5.4/lua.pc",
+                "lua5.4/lua-sources.json",
+                "lua5.4/readline.json",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_drift() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.org").unwrap();
+        let commit = |files: &[&str], parent: Option<git2::Oid>| {
+            let mut index = repo.index().unwrap();
+            for file in files {
+                let path = dir.path().join(file);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, format!("{parent:?}")).unwrap();
+                index.add_path(Path::new(file)).unwrap();
+            }
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = parent.map(|oid| repo.find_commit(oid).unwrap());
+            let parents: Vec<_> = parent.iter().collect();
+            repo.commit(None, &signature, &signature, "commit", &tree, &parents)
+                .unwrap()
+        };
+        let pinned = commit(&["lua5.4/lua-5.4.json", "glu/glu.json"], None);
+        let middle = commit(&["glu/glu.json"], Some(pinned));
+        let remote = commit(&["lua5.4/lua-5.4.patch"], Some(middle));
+
+        let drift = drift(
+            &repo,
+            pinned,
+            remote,
+            &[PathBuf::from("lua5.4/lua-5.4.json")],
+        )
+        .unwrap();
+        assert_eq!(drift.behind, 2);
+        assert_eq!(drift.changed, vec!["lua5.4/lua-5.4.patch"]);
+    }
+}
@@ -0,0 +1,165 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Manifest templates.
+//!
+//! A template is a TOML or JSON file `NAME.toml` or `NAME.json`
+//! located in the `.flathub-cli/templates` directory of the project,
+//! or in the `templates` directory of the user configuration.
+//!
+//! The variables `{{id}}`, `{{short_id}}`, `{{runtime}}` and
+//! `{{prefix}}` are substituted when instantiating.
+
+use std::path::{Component, Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::module::ModuleEntry;
+use crate::{anyerror, error::Context, AnyError, Error, Result};
+
+/// The templates directory, relative to the project or the
+/// configuration directory.
+const PROJECT_TEMPLATES_DIR: &str = ".flathub-cli/templates";
+const TEMPLATES_DIR: &str = "templates";
+
+/// A file to create in the project.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TemplateFile {
+    /// The path relative to the project.
+    pub path: String,
+    /// The content of the file.
+    pub content: String,
+}
+
+/// A manifest template.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Template {
+    /// Description of the template.
+    pub description: Option<String>,
+    /// Default modules, added before the other modules.
+    pub modules: Vec<ModuleEntry>,
+    /// The `finish-args`.
+    pub finish_args: Vec<String>,
+    /// The `build-options`.
+    pub build_options: serde_json::Map<String, serde_json::Value>,
+    /// Extra files to create in the project.
+    pub files: Vec<TemplateFile>,
+}
+
+impl Template {
+    /// Find the template `name`, first in the `project_dir` then
+    /// in the user configuration.
+    pub fn find(name: &str, project_dir: &Path) -> Result<PathBuf> {
+        if name.is_empty() || name.contains(std::path::is_separator) {
+            return Err(anyerror!(format!("Invalid template name '{name}'")));
+        }
+        let dirs = [
+            Some(project_dir.join(PROJECT_TEMPLATES_DIR)),
+            crate::xdg::config_dir().map(|dir| dir.join(TEMPLATES_DIR)),
+        ];
+        dirs.iter()
+            .flatten()
+            .flat_map(|dir| ["toml", "json"].map(|ext| dir.join(format!("{name}.{ext}"))))
+            .find(|path| path.is_file())
+            .ok_or_else(|| AnyError::context(format!("template '{name}'"), Error::NotFound))
+    }
+
+    /// Load the template at `path`, substituting the `vars`.
+    pub fn instantiate(path: &Path, vars: &[(&str, String)]) -> Result<Template> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Reading template {path:?}"))?;
+        let content = substitute(&content, vars);
+        let template: Template = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).with_context(|| format!("Parsing template {path:?}"))?
+        } else {
+            toml::from_str(&content).with_context(|| format!("Parsing template {path:?}"))?
+        };
+
+        if let Some(file) = template.files.iter().find(|file| {
+            file.path.is_empty()
+                || !Path::new(&file.path)
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+        }) {
+            return Err(anyerror!(format!(
+                "Template file path '{}' must be relative to the project",
+                file.path
+            )));
+        }
+        for module in &template.modules {
+            if let ModuleEntry::Module(module) = module {
+                for source in &module.sources {
+                    source.validate().map_err(|err| {
+                        anyerror!(format!("Invalid source in module '{}': {err}", module.name))
+                    })?;
+                }
+            }
+        }
+
+        Ok(template)
+    }
+}
+
+/// Substitute the `{{NAME}}` variables in `text`.
+fn substitute(text: &str, vars: &[(&str, String)]) -> String {
+    vars.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{{{name}}}}}"), value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instantiate() {
+        let dir = tempfile::tempdir().unwrap();
+        let templates_dir = dir.path().join(PROJECT_TEMPLATES_DIR);
+        std::fs::create_dir_all(&templates_dir).unwrap();
+        std::fs::write(
+            templates_dir.join("house.toml"),
+            r#"
+description = "House style"
+finish-args = ["--share=ipc"]
+
+[build-options]
+prefix = "{{prefix}}"
+
+[[modules]]
+name = "{{short_id}}-data"
+buildsystem = "simple"
+
+[[files]]
+path = "{{id}}.txt"
+content = "Built against {{runtime}}"
+"#,
+        )
+        .unwrap();
+
+        let path = Template::find("house", dir.path()).unwrap();
+        let template = Template::instantiate(
+            &path,
+            &[
+                ("id", "org.example.App".to_string()),
+                ("short_id", "App".to_string()),
+                ("runtime", "org.gnome.Platform//49".to_string()),
+                ("prefix", "/app".to_string()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(template.finish_args, ["--share=ipc"]);
+        assert_eq!(template.build_options["prefix"], "/app");
+        assert!(matches!(&template.modules[0], ModuleEntry::Module(m) if m.name == "App-data"));
+        assert_eq!(template.files[0].path, "org.example.App.txt");
+        assert_eq!(
+            template.files[0].content,
+            "Built against org.gnome.Platform//49"
+        );
+
+        assert!(Template::find("nope", dir.path()).is_err());
+        assert!(Template::find("../house", dir.path()).is_err());
+    }
+}
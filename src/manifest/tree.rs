@@ -0,0 +1,382 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The resolved module tree of a manifest, with the included files.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::file as manifest_file;
+use crate::flathub::SHARED_MODULES;
+
+/// The extensions of archives, stripped to find the version.
+const ARCHIVE_EXTENSIONS: &[&str] = &[
+    ".tar.gz", ".tar.xz", ".tar.bz2", ".tar.zst", ".tar.lz", ".tgz", ".tbz2", ".txz", ".tar",
+    ".zip", ".7z", ".deb", ".rpm", ".gz", ".xz", ".bz2",
+];
+
+/// A source of a module.
+#[derive(Debug, Serialize)]
+pub(crate) struct SourceSummary {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// A module of the tree.
+#[derive(Debug, Serialize)]
+pub(crate) struct ModuleNode {
+    pub name: String,
+    pub buildsystem: String,
+    /// The included file the module comes from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// Whether the module comes from shared-modules.
+    pub shared: bool,
+    pub sources: Vec<SourceSummary>,
+    /// Why the included file couldn't be loaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<ModuleNode>,
+}
+
+/// Whether the file `name` is an archive, based on its extension.
+pub(crate) fn is_archive(name: &str) -> bool {
+    ARCHIVE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// Extract the version from a git `tag`, from its first digit.
+fn version_from_tag(tag: &str) -> Option<String> {
+    let start = tag.find(|c: char| c.is_ascii_digit())?;
+
+    Some(tag[start..].replace('_', "."))
+}
+
+/// Extract the version from the file name of an archive `url`.
+fn version_from_url(url: &str) -> Option<String> {
+    let file_name = url.trim_end_matches('/').rsplit('/').next()?;
+    let file_name = ARCHIVE_EXTENSIONS
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(ext))
+        .unwrap_or(file_name);
+    // The version starts with a digit, after a `-` or `_`, and an
+    // optional `v`.
+    let start = (0..file_name.len()).find(|&idx| {
+        let (before, rest) = file_name.split_at_checked(idx).unwrap_or_default();
+        let before = before.strip_suffix(['v', 'V']).unwrap_or(before);
+        rest.starts_with(|c: char| c.is_ascii_digit())
+            && (before.is_empty() || before.ends_with(['-', '_']))
+    })?;
+    let version: String = file_name[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '.')
+        .collect();
+    let version = version.trim_end_matches('.');
+
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// Replace the occurrences of the version `old` with `new` in `text`
+/// that aren't part of a name or of a longer version. `old` may follow
+/// a `v`, like in a tag.
+fn replace_version(text: &str, old: &str, new: &str) -> String {
+    let is_name = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(idx) = rest.find(old) {
+        let (before, after) = (&rest[..idx], &rest[idx + old.len()..]);
+        let mut prev = before.chars().rev();
+        let prev_ok = match prev.next() {
+            Some('v' | 'V') => !is_name(prev.next()),
+            c => !is_name(c),
+        };
+        let mut next = after.chars();
+        let next_ok = match next.next() {
+            Some('.') => !next.next().is_some_and(|c| c.is_ascii_digit()),
+            c => !is_name(c),
+        };
+        result.push_str(before);
+        result.push_str(if prev_ok && next_ok { new } else { old });
+        rest = after;
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Replace the version `old` with `new` in `text`, an URL or a tag.
+/// Only the file name and the directories named after the version, or
+/// its `MAJOR.MINOR`, are changed.
+pub(crate) fn substitute_version(text: &str, old: &str, new: &str) -> String {
+    let major_minor = |version: &str| {
+        let parts: Vec<_> = version.split('.').collect();
+        (parts.len() > 2).then(|| format!("{}.{}", parts[0], parts[1]))
+    };
+    let (dirs, file) = match text.rsplit_once('/') {
+        Some((dirs, file)) => (Some(dirs), file),
+        None => (None, text),
+    };
+    let file = replace_version(file, old, new);
+    let Some(dirs) = dirs else {
+        return file;
+    };
+    let old_major_minor = major_minor(old);
+    let new_major_minor = major_minor(new);
+    let dirs: Vec<_> = dirs
+        .split('/')
+        .map(|dir| match (&old_major_minor, &new_major_minor) {
+            (Some(old), Some(new)) if dir == old => new.clone(),
+            _ if dir.trim_start_matches(['v', 'V']) == old => replace_version(dir, old, new),
+            _ => dir.to_string(),
+        })
+        .collect();
+
+    format!("{}/{file}", dirs.join("/"))
+}
+
+/// Summarize the source `value`. A string is an included source file.
+pub(crate) fn source_summary(value: &JsonValue) -> SourceSummary {
+    let Some(source) = value.as_object() else {
+        return SourceSummary {
+            type_: "include".to_string(),
+            version: None,
+        };
+    };
+    let version = source
+        .get("tag")
+        .and_then(JsonValue::as_str)
+        .and_then(version_from_tag)
+        .or_else(|| {
+            source
+                .get("url")
+                .and_then(JsonValue::as_str)
+                .and_then(version_from_url)
+        });
+
+    SourceSummary {
+        type_: source
+            .get("type")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("archive")
+            .to_string(),
+        version,
+    }
+}
+
+/// Whether `path` is in shared-modules.
+pub(crate) fn is_shared(path: &Path) -> bool {
+    path.components()
+        .any(|component| component.as_os_str() == SHARED_MODULES)
+}
+
+/// Resolve the module `value` from the file in `dir`. `file` and
+/// `shared` tell where it comes from.
+fn resolve_module(
+    value: &JsonValue,
+    dir: &Path,
+    file: Option<&Path>,
+    shared: bool,
+) -> Vec<ModuleNode> {
+    if let JsonValue::String(include) = value {
+        let path = dir.join(include);
+        let shared = shared || is_shared(Path::new(include));
+        return match manifest_file::load(&path) {
+            Ok(JsonValue::Array(modules)) => modules
+                .iter()
+                .flat_map(|module| {
+                    resolve_module(module, path.parent().unwrap_or(dir), Some(&path), shared)
+                })
+                .collect(),
+            Ok(module) => {
+                resolve_module(&module, path.parent().unwrap_or(dir), Some(&path), shared)
+            }
+            Err(err) => vec![ModuleNode {
+                name: include.to_string(),
+                buildsystem: String::default(),
+                file: Some(path),
+                shared,
+                sources: vec![],
+                error: Some(err.to_string()),
+                modules: vec![],
+            }],
+        };
+    }
+
+    vec![ModuleNode {
+        name: manifest_file::module_name(value)
+            .unwrap_or_default()
+            .to_string(),
+        buildsystem: value
+            .get("buildsystem")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("autotools")
+            .to_string(),
+        file: file.map(Path::to_path_buf),
+        shared,
+        sources: value
+            .get("sources")
+            .and_then(JsonValue::as_array)
+            .map(|sources| sources.iter().map(source_summary).collect())
+            .unwrap_or_default(),
+        error: None,
+        modules: resolve_modules(value, dir, file, shared),
+    }]
+}
+
+/// Resolve the modules of the manifest or module `value`.
+fn resolve_modules(
+    value: &JsonValue,
+    dir: &Path,
+    file: Option<&Path>,
+    shared: bool,
+) -> Vec<ModuleNode> {
+    manifest_file::modules(value)
+        .iter()
+        .flat_map(|module| resolve_module(module, dir, file, shared))
+        .collect()
+}
+
+/// Whether any of `modules` comes from shared-modules.
+pub(crate) fn uses_shared_modules(modules: &[ModuleNode]) -> bool {
+    modules
+        .iter()
+        .any(|module| module.shared || uses_shared_modules(&module.modules))
+}
+
+/// Return the included files of `modules` that come from
+/// shared-modules, without duplicates.
+pub(crate) fn shared_files(modules: &[ModuleNode]) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for module in modules {
+        if let Some(file) = module.file.as_ref().filter(|_| module.shared) {
+            if !files.contains(file) {
+                files.push(file.to_path_buf());
+            }
+        }
+        for file in shared_files(&module.modules) {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+
+    files
+}
+
+/// Make the included files of `modules` relative to `dir`.
+fn strip_dir(modules: &mut [ModuleNode], dir: &Path) {
+    for module in modules {
+        if let Some(file) = module.file.take() {
+            module.file = Some(
+                file.strip_prefix(dir)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(file),
+            );
+        }
+        strip_dir(&mut module.modules, dir);
+    }
+}
+
+/// Resolve the module tree of `manifest`, loaded from `dir`. Included
+/// files that can't be loaded are in the tree with an error.
+pub(crate) fn resolve(manifest: &JsonValue, dir: &Path) -> Vec<ModuleNode> {
+    let mut modules = resolve_modules(manifest, dir, None, false);
+    strip_dir(&mut modules, dir);
+
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(
+            version_from_url("https://example.org/fltk-1.4.1-source.tar.bz2").as_deref(),
+            Some("1.4.1")
+        );
+        assert_eq!(
+            version_from_url("https://github.com/lua/lua/archive/refs/tags/v5.4.6.tar.gz")
+                .as_deref(),
+            Some("5.4.6")
+        );
+        assert_eq!(
+            version_from_url("https://example.org/libfoo2_0.9.tar.xz").as_deref(),
+            Some("0.9")
+        );
+        assert_eq!(version_from_url("https://example.org/app.zip"), None);
+        assert_eq!(version_from_tag("release-2_1_0").as_deref(), Some("2.1.0"));
+        assert_eq!(version_from_tag("main"), None);
+        assert_eq!(
+            substitute_version(
+                "https://download.gnome.org/sources/gtk/4.14/gtk-4.14.2.tar.xz",
+                "4.14.2",
+                "4.16.0"
+            ),
+            "https://download.gnome.org/sources/gtk/4.16/gtk-4.16.0.tar.xz"
+        );
+        assert_eq!(substitute_version("v1.0", "1.0", "1.1"), "v1.1");
+        assert_eq!(
+            substitute_version("https://app2.example.org/app2/app2-2.tar.gz", "2", "3"),
+            "https://app2.example.org/app2/app2-3.tar.gz"
+        );
+        assert_eq!(
+            substitute_version(
+                "https://github.com/example/app/releases/download/v1.2.3/app-1.2.3.tar.gz",
+                "1.2.3",
+                "1.3.0"
+            ),
+            "https://github.com/example/app/releases/download/v1.3.0/app-1.3.0.tar.gz"
+        );
+        assert_eq!(
+            substitute_version("https://example.org/app-1.2.10.tar.gz", "1.2", "1.3"),
+            "https://example.org/app-1.2.10.tar.gz"
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("shared-modules/lua5.4")).unwrap();
+        std::fs::write(
+            dir.path().join("shared-modules/lua5.4/lua-5.4.json"),
+            r#"{"name": "lua-5.4", "buildsystem": "simple",
+                "sources": [{"type": "archive", "url": "https://www.lua.org/ftp/lua-5.4.6.tar.gz"}]}"#,
+        )
+        .unwrap();
+        let manifest = json!({
+            "modules": [
+                "shared-modules/lua5.4/lua-5.4.json",
+                "missing.json",
+                {
+                    "name": "app",
+                    "buildsystem": "meson",
+                    "sources": [{"type": "git", "url": "https://example.org/app.git", "tag": "v1.0"}]
+                }
+            ]
+        });
+
+        let tree = resolve(&manifest, dir.path());
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree[0].name, "lua-5.4");
+        assert!(tree[0].shared);
+        assert_eq!(
+            tree[0].file.as_deref(),
+            Some(Path::new("shared-modules/lua5.4/lua-5.4.json"))
+        );
+        assert_eq!(tree[0].sources[0].version.as_deref(), Some("5.4.6"));
+        assert!(tree[1].error.is_some());
+        assert_eq!(tree[2].buildsystem, "meson");
+        assert!(!tree[2].shared);
+        assert_eq!(tree[2].sources[0].type_, "git");
+        assert_eq!(tree[2].sources[0].version.as_deref(), Some("1.0"));
+    }
+}
@@ -1,65 +1,43 @@
-// SPDX-FileCopyrightText: 2023 Hubert Figuière
+// SPDX-FileCopyrightText: 2023-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 //! Implements the Text UI
 
 mod prompt;
+mod ui;
 
-use dialoguer::{theme::ColorfulTheme, Input, Select};
+use std::path::Path;
 
 use super::config;
+use super::finish_args::{FinishArgs, Profile};
 use super::module::{Buildsystem, Module, ModuleEntry};
+use super::shared_modules::{self, SharedModule};
 use super::{PackageType, Runtime, Sdk, SdkExtension};
 pub(crate) use prompt::Prompt;
+#[cfg(test)]
+pub(crate) use ui::ScriptedUi;
+pub(crate) use ui::{NonInteractiveUi, TerminalUi, Ui};
 
-fn prompt_sdk_ext_version(idx: usize) -> Option<SdkExtension> {
+fn prompt_sdk_ext_version(ui: &mut dyn Ui, idx: usize) -> Option<SdkExtension> {
     match idx {
         1 => Some(SdkExtension::Rust),
         2 => Some(SdkExtension::RustNightly),
-        3 => Input::<String>::new()
-            .with_prompt("Java SDK version")
-            .interact()
-            .ok()
-            .map(SdkExtension::Java),
-        4 => Input::<String>::new()
-            .with_prompt("Node version")
-            .interact()
-            .ok()
-            .map(SdkExtension::Node),
-        5 => Input::<String>::new()
-            .with_prompt("PHP version")
-            .interact()
-            .ok()
-            .map(SdkExtension::Php),
+        3 => ui.input("Java SDK version").map(SdkExtension::Java),
+        4 => ui.input("Node version").map(SdkExtension::Node),
+        5 => ui.input("PHP version").map(SdkExtension::Php),
         6 => Some(SdkExtension::GoLang),
         7 => Some(SdkExtension::TexLive),
-        8 => Input::<String>::new()
-            .with_prompt("LLVM version")
-            .interact()
-            .ok()
-            .map(SdkExtension::Llvm),
-        9 => Input::<String>::new()
-            .with_prompt(".Net version")
-            .interact()
-            .ok()
-            .map(SdkExtension::DotNet),
-        10 => Input::<String>::new()
-            .with_prompt("Mono version")
-            .interact()
-            .ok()
-            .map(SdkExtension::Mono),
-        11 => Input::<String>::new()
-            .with_prompt("gcc version")
-            .interact()
-            .ok()
-            .map(SdkExtension::Gcc),
+        8 => ui.input("LLVM version").map(SdkExtension::Llvm),
+        9 => ui.input(".Net version").map(SdkExtension::DotNet),
+        10 => ui.input("Mono version").map(SdkExtension::Mono),
+        11 => ui.input("gcc version").map(SdkExtension::Gcc),
         _ => None,
     }
 }
 
 impl Prompt for SdkExtension {
-    fn prompt() -> Option<SdkExtension> {
+    fn prompt(ui: &mut dyn Ui) -> Option<SdkExtension> {
         let choices = &[
             "None",
             "Rust (stable)",
@@ -75,37 +53,60 @@ impl Prompt for SdkExtension {
             "Gcc",
         ];
 
-        Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select the SDK Extension:")
-            .default(0)
-            .items(choices)
-            .interact()
-            .ok()
+        ui.select("Select the SDK Extension:", choices, 0)
             .and_then(|selection| match selection {
-                1..=11 => prompt_sdk_ext_version(selection),
+                1..=11 => prompt_sdk_ext_version(ui, selection),
                 _ => None,
             })
     }
 }
 
+/// Prompt for a list of values, until an empty one. `None` if the
+/// list is empty.
+fn prompt_list(ui: &mut dyn Ui, prompt: &str) -> Option<Vec<String>> {
+    let mut values = vec![];
+    while let Some(value) = ui.input_optional(prompt).filter(|value| !value.is_empty()) {
+        values.push(value);
+    }
+
+    (!values.is_empty()).then_some(values)
+}
+
 impl Prompt for Module {
-    fn prompt() -> Option<Module> {
-        let name = Input::<String>::new()
-            .with_prompt("Module name")
-            .interact()
-            .ok()?;
-        let buildsystem = Buildsystem::prompt()?;
-
-        Some(Module {
+    fn prompt(ui: &mut dyn Ui) -> Option<Module> {
+        let name = ui.input("Module name")?;
+        let buildsystem = Buildsystem::prompt(ui)?;
+
+        let mut module = Module {
             name,
             buildsystem,
             ..Module::default()
-        })
+        };
+        match module.buildsystem {
+            Buildsystem::Simple => {
+                module.build_commands = prompt_list(ui, "Build command (empty to finish)");
+            }
+            Buildsystem::Autotools => {
+                module.config_opts = prompt_list(ui, "Configure option (empty to finish)");
+                module.no_autogen = ui.confirm("Skip autogen?", false).filter(|b| *b);
+            }
+            Buildsystem::Cmake | Buildsystem::CmakeNinja => {
+                module.config_opts = prompt_list(ui, "Configure option (empty to finish)");
+                module.builddir = ui
+                    .confirm("Build in a separate directory?", true)
+                    .filter(|b| *b);
+            }
+            Buildsystem::Meson | Buildsystem::Qmake => {
+                module.config_opts = prompt_list(ui, "Configure option (empty to finish)");
+            }
+        }
+
+        Some(module)
     }
 }
 
 impl Prompt for Buildsystem {
-    fn prompt() -> Option<Buildsystem> {
+    fn prompt(ui: &mut dyn Ui) -> Option<Buildsystem> {
         use Buildsystem::*;
 
         let choices = &[
@@ -117,12 +118,7 @@ impl Prompt for Buildsystem {
             "qmake",
         ];
 
-        Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select a build system:")
-            .default(0)
-            .items(choices)
-            .interact()
-            .ok()
+        ui.select("Select a build system:", choices, 0)
             .and_then(|selection| match selection {
                 0 => Some(Autotools),
                 1 => Some(Cmake),
@@ -135,25 +131,52 @@ impl Prompt for Buildsystem {
     }
 }
 
-impl Prompt for ModuleEntry {
-    fn prompt() -> Option<ModuleEntry> {
-        let mut choices = vec!["None", "Custom"];
+/// Search the shared-modules `catalogue` until a keyword matches, and
+/// select one of the matches. An empty keyword lists all the modules.
+fn prompt_shared_module(ui: &mut dyn Ui, catalogue: &[SharedModule]) -> Option<ModuleEntry> {
+    if catalogue.is_empty() {
+        println!("No shared-modules checkout. Run `flathub-cli module search --update`.");
+        return None;
+    }
+    loop {
+        let keyword = ui.input_optional("Search shared-modules (empty to list all)")?;
+        let matches = shared_modules::search(catalogue, &keyword);
+        if matches.is_empty() {
+            println!("No module matching '{keyword}'.");
+            continue;
+        }
+        let items: Vec<String> = matches.iter().map(ToString::to_string).collect();
+        let items: Vec<&str> = items.iter().map(String::as_str).collect();
+
+        return ui
+            .select("Select a module:", &items, 0)
+            .map(|idx| ModuleEntry::SharedModule(matches[idx].path.clone()));
+    }
+}
+
+impl ModuleEntry {
+    /// Prompt for a module. shared-modules is searched in the project
+    /// at `project_dir`, or in the cache.
+    pub(crate) fn prompt_in(ui: &mut dyn Ui, project_dir: Option<&Path>) -> Option<ModuleEntry> {
+        let mut choices = vec!["None", "Custom", "Search shared-modules"];
         config::DEFAULT_MODULES.keys().for_each(|k| choices.push(k));
 
-        Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select a module:")
-            .default(0)
-            .items(&choices)
-            .interact()
-            .ok()
+        ui.select("Select a module:", &choices, 0)
             .and_then(|selection| match selection {
                 0 => None,
-                1 => Module::prompt().map(ModuleEntry::Module),
+                1 => Module::prompt(ui).map(|module| ModuleEntry::Module(Box::new(module))),
+                2 => prompt_shared_module(ui, &shared_modules::catalogue(project_dir)),
                 _ => config::DEFAULT_MODULES.get(choices[selection]).cloned(),
             })
     }
 }
 
+impl Prompt for ModuleEntry {
+    fn prompt(ui: &mut dyn Ui) -> Option<ModuleEntry> {
+        ModuleEntry::prompt_in(ui, None)
+    }
+}
+
 fn prompt_runtime_version(idx: usize) -> Option<Runtime> {
     match idx {
         0 => Some(Runtime::Freedesktop(
@@ -167,15 +190,10 @@ fn prompt_runtime_version(idx: usize) -> Option<Runtime> {
 }
 
 impl Prompt for Runtime {
-    fn prompt() -> Option<Runtime> {
+    fn prompt(ui: &mut dyn Ui) -> Option<Runtime> {
         let choices = &["Freedesktop", "GNOME", "KDE/Qt 5.15", "Qt 6", "Other"];
 
-        Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select the runtime:")
-            .default(0)
-            .items(choices)
-            .interact()
-            .ok()
+        ui.select("Select the runtime:", choices, 0)
             .and_then(|selection| {
                 match selection {
                     0..=3 => prompt_runtime_version(selection),
@@ -201,15 +219,10 @@ fn prompt_sdk_version(idx: usize) -> Option<Sdk> {
 }
 
 impl Prompt for Sdk {
-    fn prompt() -> Option<Sdk> {
+    fn prompt(ui: &mut dyn Ui) -> Option<Sdk> {
         let choices = &["Freedesktop", "GNOME", "KDE/Qt 5.15", "Qt 6"];
 
-        Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select the SDK:")
-            .default(0)
-            .items(choices)
-            .interact()
-            .ok()
+        ui.select("Select the SDK:", choices, 0)
             .and_then(|selection| match selection {
                 0..=3 => prompt_sdk_version(selection),
                 _ => None,
@@ -218,15 +231,10 @@ impl Prompt for Sdk {
 }
 
 impl Prompt for PackageType {
-    fn prompt() -> Option<PackageType> {
+    fn prompt(ui: &mut dyn Ui) -> Option<PackageType> {
         let choices = &["Application", "Linux Audio plugin", "GIMP Plugin"];
 
-        Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select the type of package:")
-            .default(0)
-            .items(choices)
-            .interact()
-            .ok()
+        ui.select("Select the type of package:", choices, 0)
             .and_then(|selection| match selection {
                 0 => Some(PackageType::Application),
                 1 => Some(PackageType::LinuxAudioPlugin),
@@ -235,3 +243,70 @@ impl Prompt for PackageType {
             })
     }
 }
+
+impl Prompt for FinishArgs {
+    fn prompt(ui: &mut dyn Ui) -> Option<FinishArgs> {
+        let questions = [
+            ("Graphical application?", Profile::Gui, true),
+            ("Play or record audio?", Profile::Audio, false),
+            ("Access the network?", Profile::Network, false),
+        ];
+
+        let mut finish_args = FinishArgs::default();
+        for (question, profile, default) in &questions {
+            if ui.confirm(question, *default)? {
+                finish_args.add_profile(profile);
+            }
+        }
+
+        Some(finish_args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_module() {
+        // An empty answer ends the list, it isn't skipped.
+        let mut ui = ScriptedUi::new(&["app", "simple", "make", "make install", "", "extra"]);
+        let module = Module::prompt(&mut ui).unwrap();
+        assert_eq!(
+            module.build_commands,
+            Some(vec!["make".to_string(), "make install".to_string()])
+        );
+        assert!(!ui.is_exhausted());
+
+        // The name is required, an empty answer is skipped.
+        let mut ui = ScriptedUi::new(&["", "lib", "meson", ""]);
+        let module = Module::prompt(&mut ui).unwrap();
+        assert_eq!(module.name, "lib");
+        assert_eq!(module.config_opts, None);
+        assert!(ui.is_exhausted());
+    }
+    #[test]
+    fn test_prompt_shared_module() {
+        let catalogue: Vec<_> = ["lua5.4/lua-5.4.json", "libsecret/libsecret.json"]
+            .iter()
+            .map(|path| SharedModule {
+                path: path.to_string(),
+                name: path.split('/').next().unwrap().to_string(),
+                version: None,
+            })
+            .collect();
+
+        let mut ui = ScriptedUi::new(&["gtk", "secret", "0"]);
+        assert!(matches!(
+            prompt_shared_module(&mut ui, &catalogue),
+            Some(ModuleEntry::SharedModule(path)) if path == "libsecret/libsecret.json"
+        ));
+        // An empty keyword lists all the modules.
+        let mut ui = ScriptedUi::new(&["", "1"]);
+        assert!(matches!(
+            prompt_shared_module(&mut ui, &catalogue),
+            Some(ModuleEntry::SharedModule(path)) if path == "libsecret/libsecret.json"
+        ));
+        assert!(prompt_shared_module(&mut ScriptedUi::new(&["gtk"]), &catalogue).is_none());
+    }
+}
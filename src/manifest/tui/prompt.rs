@@ -1,10 +1,12 @@
-// SPDX-FileCopyrightText: 2020-2023 Hubert Figuière
+// SPDX-FileCopyrightText: 2020-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use super::Ui;
+
 pub trait Prompt
 where
     Self: Sized,
 {
-    fn prompt() -> Option<Self>;
+    fn prompt(ui: &mut dyn Ui) -> Option<Self>;
 }
@@ -0,0 +1,162 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! The user interface used by the prompts.
+
+#[cfg(test)]
+use std::collections::VecDeque;
+
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
+
+/// The user interface to answer prompts. `None` means no answer,
+/// either cancelled or unavailable.
+pub(crate) trait Ui {
+    /// Select one of `items`. Return the index.
+    fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> Option<usize>;
+    /// Input a non empty string.
+    fn input(&mut self, prompt: &str) -> Option<String>;
+    /// Input a string that can be empty.
+    fn input_optional(&mut self, prompt: &str) -> Option<String>;
+    /// Confirm a yes / no question.
+    fn confirm(&mut self, prompt: &str, default: bool) -> Option<bool>;
+}
+
+/// The terminal user interface.
+#[derive(Default)]
+pub(crate) struct TerminalUi {
+    theme: ColorfulTheme,
+}
+
+impl Ui for TerminalUi {
+    fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> Option<usize> {
+        Select::with_theme(&self.theme)
+            .with_prompt(prompt)
+            .default(default)
+            .items(items)
+            .interact()
+            .ok()
+    }
+
+    fn input(&mut self, prompt: &str) -> Option<String> {
+        Input::<String>::with_theme(&self.theme)
+            .with_prompt(prompt)
+            .interact()
+            .ok()
+    }
+
+    fn input_optional(&mut self, prompt: &str) -> Option<String> {
+        Input::<String>::with_theme(&self.theme)
+            .with_prompt(prompt)
+            .allow_empty(true)
+            .interact()
+            .ok()
+    }
+
+    fn confirm(&mut self, prompt: &str, default: bool) -> Option<bool> {
+        Confirm::with_theme(&self.theme)
+            .with_prompt(prompt)
+            .default(default)
+            .interact()
+            .ok()
+    }
+}
+
+/// A user interface that never answers. For non interactive use.
+pub(crate) struct NonInteractiveUi;
+
+impl Ui for NonInteractiveUi {
+    fn select(&mut self, _prompt: &str, _items: &[&str], _default: usize) -> Option<usize> {
+        None
+    }
+
+    fn input(&mut self, _prompt: &str) -> Option<String> {
+        None
+    }
+
+    fn input_optional(&mut self, _prompt: &str) -> Option<String> {
+        None
+    }
+
+    fn confirm(&mut self, _prompt: &str, _default: bool) -> Option<bool> {
+        None
+    }
+}
+
+/// A user interface that answers from a script, in order.
+///
+/// For a selection, the answer is either the item label (case
+/// insensitive) or its index. For a confirmation, it is `yes` or
+/// `no`. Like the terminal, empty answers are skipped for a non empty
+/// input. Once the script is exhausted, there is no answer.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct ScriptedUi {
+    answers: VecDeque<String>,
+}
+
+#[cfg(test)]
+impl ScriptedUi {
+    pub fn new<S: ToString>(answers: &[S]) -> ScriptedUi {
+        ScriptedUi {
+            answers: answers.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Whether all the answers have been consumed.
+    pub fn is_exhausted(&self) -> bool {
+        self.answers.is_empty()
+    }
+}
+
+#[cfg(test)]
+impl Ui for ScriptedUi {
+    fn select(&mut self, _prompt: &str, items: &[&str], _default: usize) -> Option<usize> {
+        let answer = self.answers.pop_front()?;
+        items
+            .iter()
+            .position(|item| item.eq_ignore_ascii_case(&answer))
+            .or_else(|| answer.parse().ok().filter(|idx| *idx < items.len()))
+    }
+
+    fn input(&mut self, _prompt: &str) -> Option<String> {
+        loop {
+            let answer = self.answers.pop_front()?;
+            if !answer.is_empty() {
+                return Some(answer);
+            }
+        }
+    }
+
+    fn input_optional(&mut self, _prompt: &str) -> Option<String> {
+        self.answers.pop_front()
+    }
+
+    fn confirm(&mut self, _prompt: &str, _default: bool) -> Option<bool> {
+        match self.answers.pop_front()?.as_str() {
+            "y" | "yes" => Some(true),
+            "n" | "no" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_ui() {
+        let mut ui = ScriptedUi::new(&["gnome", "2", "", "foo", "", "yes", "bar"]);
+        let items = &["Freedesktop", "GNOME", "KDE"];
+
+        assert_eq!(ui.select("", items, 0), Some(1));
+        assert_eq!(ui.select("", items, 0), Some(2));
+        assert_eq!(ui.input(""), Some("foo".to_string()));
+        assert_eq!(ui.input_optional(""), Some("".to_string()));
+        assert_eq!(ui.confirm("", false), Some(true));
+        assert_eq!(ui.select("", items, 0), None);
+        assert!(ui.is_exhausted());
+        assert_eq!(ui.input(""), None);
+    }
+}
@@ -1,8 +1,8 @@
-// SPDX-FileCopyrightText: 2023 Hubert Figuière
+// SPDX-FileCopyrightText: 2023-2026 Hubert Figuière
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::{Error, Result};
+use crate::{anyerror, error::Context, Error, Result};
 
 /// Check if the git repository at `repo` exists.
 pub fn check_repo_exist<P>(repo: P) -> bool
@@ -35,6 +35,27 @@ where
     Ok(())
 }
 
+/// Remove `path` from the index of `repo`, if it is tracked, and from
+/// the working directory.
+pub(crate) fn remove_path_from_repo<P>(repo: &git2::Repository, path: P) -> Result<()>
+where
+    P: AsRef<std::path::Path>,
+{
+    let base_path = repo.path().parent().ok_or(Error::InvalidArgument)?;
+    let relative_path = path
+        .as_ref()
+        .strip_prefix(base_path)
+        .or(Err(Error::InvalidArgument))?;
+    let mut index = repo.index()?;
+    if index.get_path(relative_path, 0).is_some() {
+        index.remove_path(relative_path)?;
+        index.write()?;
+    }
+    std::fs::remove_file(path.as_ref()).with_context(|| format!("Removing {relative_path:?}"))?;
+
+    Ok(())
+}
+
 pub(crate) fn add_submodule_to_repo<P>(repo: &git2::Repository, url: &str, path: P) -> Result<()>
 where
     P: AsRef<std::path::Path>,
@@ -46,3 +67,250 @@ where
 
     Ok(())
 }
+
+/// Remove the section of the submodule `name` from the `.gitmodules`
+/// `content`.
+fn remove_gitmodules_section(content: &str, name: &str) -> String {
+    let header = format!("[submodule \"{name}\"]");
+    let mut in_section = false;
+
+    content
+        .lines()
+        .filter(|line| {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_section = line == header;
+            }
+            !in_section
+        })
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Remove the submodule at `path`: its `.gitmodules` entry, its
+/// configuration, the index entry and the checkout. The changes are
+/// staged.
+pub(crate) fn remove_submodule<P>(repo: &git2::Repository, path: P) -> Result<()>
+where
+    P: AsRef<std::path::Path>,
+{
+    let path = path.as_ref();
+    let workdir = repo.workdir().ok_or(Error::InvalidArgument)?;
+    let name = repo
+        .submodules()?
+        .iter()
+        .find(|submodule| submodule.path() == path)
+        .and_then(|submodule| submodule.name().ok().map(str::to_string))
+        .ok_or_else(|| anyerror!(format!("No submodule at {path:?}")))?;
+
+    let mut index = repo.index()?;
+    let gitmodules = workdir.join(".gitmodules");
+    let content =
+        std::fs::read_to_string(&gitmodules).with_context(|| format!("Reading {gitmodules:?}"))?;
+    let content = remove_gitmodules_section(&content, &name);
+    if content.trim().is_empty() {
+        std::fs::remove_file(&gitmodules)?;
+        index.remove_path(std::path::Path::new(".gitmodules"))?;
+    } else {
+        std::fs::write(&gitmodules, content).with_context(|| format!("Writing {gitmodules:?}"))?;
+        index.add_path(std::path::Path::new(".gitmodules"))?;
+    }
+    index.remove_path(path)?;
+    index.write()?;
+
+    let mut config = repo.config()?;
+    for key in ["url", "active", "update", "branch"] {
+        // They are only set once the submodule is initialized.
+        let _ = config.remove(&format!("submodule.{name}.{key}"));
+    }
+    let checkout = workdir.join(path);
+    if checkout.exists() {
+        std::fs::remove_dir_all(&checkout).with_context(|| format!("Removing {checkout:?}"))?;
+    }
+    let modules_dir = repo.path().join("modules").join(&name);
+    if modules_dir.exists() {
+        std::fs::remove_dir_all(&modules_dir)
+            .with_context(|| format!("Removing {modules_dir:?}"))?;
+    }
+
+    Ok(())
+}
+
+/// List the references of the remote repository at `url`, with the
+/// peeled tags as `refs/tags/TAG^{}`.
+pub(crate) fn ls_remote(url: &str) -> Result<Vec<(String, git2::Oid)>> {
+    let mut remote = git2::Remote::create_detached(url)?;
+    remote.connect(git2::Direction::Fetch)?;
+    let heads = remote
+        .list()?
+        .iter()
+        .map(|head| (head.name().to_string(), head.oid()))
+        .collect();
+    remote.disconnect()?;
+
+    Ok(heads)
+}
+
+/// Return the commit of the `tag` in the `heads` listed by
+/// `ls_remote`. Annotated tags are peeled.
+pub(crate) fn tag_commit(heads: &[(String, git2::Oid)], tag: &str) -> Option<git2::Oid> {
+    let find = |name: &str| {
+        heads
+            .iter()
+            .find(|(head, _)| head == name)
+            .map(|(_, oid)| *oid)
+    };
+
+    find(&format!("refs/tags/{tag}^{{}}")).or_else(|| find(&format!("refs/tags/{tag}")))
+}
+
+/// Return the commit of the `branch` in the `heads` listed by
+/// `ls_remote`.
+pub(crate) fn branch_commit(heads: &[(String, git2::Oid)], branch: &str) -> Option<git2::Oid> {
+    let name = format!("refs/heads/{branch}");
+
+    heads
+        .iter()
+        .find(|(head, _)| *head == name)
+        .map(|(_, oid)| *oid)
+}
+
+/// Return the commit at `HEAD` of `repo`, `None` if there is none yet.
+fn head_commit(repo: &git2::Repository) -> Result<Option<git2::Commit<'_>>> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(err) if err.code() == git2::ErrorCode::UnbornBranch => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Commit `tree` on `parent` as `HEAD` with `message`, using the
+/// signature from the git configuration.
+fn commit_tree(
+    repo: &git2::Repository,
+    tree: git2::Oid,
+    parent: Option<git2::Commit>,
+    message: &str,
+) -> Result<git2::Oid> {
+    let tree = repo.find_tree(tree)?;
+    let signature = repo.signature()?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let oid = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )?;
+
+    Ok(oid)
+}
+
+/// Commit the index of `repo` on `HEAD` with `message`, with all the
+/// staged changes.
+pub(crate) fn commit(repo: &git2::Repository, message: &str) -> Result<git2::Oid> {
+    let tree = repo.index()?.write_tree()?;
+
+    commit_tree(repo, tree, head_commit(repo)?, message)
+}
+
+/// Commit the staged `paths` of `repo` on `HEAD` with `message`. The
+/// other staged changes are left in the index.
+pub(crate) fn commit_paths<P>(
+    repo: &git2::Repository,
+    paths: &[P],
+    message: &str,
+) -> Result<git2::Oid>
+where
+    P: AsRef<std::path::Path>,
+{
+    let base_path = repo.path().parent().ok_or(Error::InvalidArgument)?;
+    let index = repo.index()?;
+    let parent = head_commit(repo)?;
+    let mut commit_index = git2::Index::new()?;
+    if let Some(ref parent) = parent {
+        commit_index.read_tree(&parent.tree()?)?;
+    }
+    for path in paths {
+        let relative_path = path
+            .as_ref()
+            .strip_prefix(base_path)
+            .or(Err(Error::InvalidArgument))?;
+        match index.get_path(relative_path, 0) {
+            Some(entry) => commit_index.add(&entry)?,
+            None => commit_index.remove_path(relative_path)?,
+        }
+    }
+    let tree = commit_index.write_tree_to(repo)?;
+
+    commit_tree(repo, tree, parent, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.org").unwrap();
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        for file in ["manifest.json", "other.txt"] {
+            std::fs::write(workdir.join(file), file).unwrap();
+            add_path_to_repo(&repo, workdir.join(file)).unwrap();
+        }
+
+        let commit = commit_paths(&repo, &[workdir.join("manifest.json")], "Update").unwrap();
+        let tree = repo.find_commit(commit).unwrap().tree().unwrap();
+        assert!(tree.get_name("manifest.json").is_some());
+        assert!(tree.get_name("other.txt").is_none());
+        // other.txt is still staged.
+        let diff = repo.diff_tree_to_index(Some(&tree), None, None).unwrap();
+        assert_eq!(diff.deltas().len(), 1);
+    }
+
+    #[test]
+    fn test_tag_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.org").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let object = repo.find_object(commit, None).unwrap();
+        repo.tag("v1.0", &object, &signature, "Version 1.0", false)
+            .unwrap();
+        repo.tag_lightweight("v1.1", &object, false).unwrap();
+
+        let heads = ls_remote(&dir.path().to_string_lossy()).unwrap();
+        assert_eq!(tag_commit(&heads, "v1.0"), Some(commit));
+        assert_eq!(tag_commit(&heads, "v1.1"), Some(commit));
+        assert_eq!(tag_commit(&heads, "v2.0"), None);
+    }
+
+    #[test]
+    fn test_remove_gitmodules_section() {
+        let content = r#"[submodule "shared-modules"]
+	path = shared-modules
+	url = https://github.com/flathub/shared-modules.git
+[submodule "other"]
+	path = other
+	url = https://example.org/other.git
+"#;
+        assert_eq!(
+            remove_gitmodules_section(content, "shared-modules"),
+            "[submodule \"other\"]\n\tpath = other\n\turl = https://example.org/other.git\n"
+        );
+        assert_eq!(
+            remove_gitmodules_section(content, "other").lines().count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,28 @@
+// SPDX-FileCopyrightText: 2026 Hubert Figuière
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! XDG base directories for `flathub-cli`.
+
+use std::path::PathBuf;
+
+const APP_DIR: &str = "flathub-cli";
+
+/// Return the base directory from the environment variable `var`,
+/// or `fallback` relative to the home directory.
+fn base_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+}
+
+/// Return the configuration directory for `flathub-cli`.
+pub fn config_dir() -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR))
+}
+
+/// Return the cache directory for `flathub-cli`.
+pub fn cache_dir() -> Option<PathBuf> {
+    base_dir("XDG_CACHE_HOME", ".cache").map(|dir| dir.join(APP_DIR))
+}